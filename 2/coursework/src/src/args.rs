@@ -6,11 +6,22 @@ FLAGS:
         Show this message.
     -p, --pause
         Launch paused.
+    -c, --continue
+        Resume the game saved with `f` to the first level path with `.save` appended.
+    -D, --demo
+        Let the computer play, restarting the level once it is over.
+    --open
+        Generator: leave out the wall around the cave.
+    --debug
+        Game: show the last tick and the object under a cursor moved with `u`, `n`, `e` and `i`.
+        `k` advances a single tick, even when paused.
 OPTIONS:
     -l, --level <string>
-        Required.
-        Specify a level to run.
+        Required, except for the spectator.
+        Specify a level or a level pack to run.
+        BDCFF caves are imported, their time limit counts ticks instead of seconds.
         Can be used multiple times.
+        The generator writes a new level to the first one.
     -m, --mode <string>
         * gui
         * tui (default)
@@ -19,11 +30,41 @@ OPTIONS:
     -r, --run <string>
         * g / b / game (default)
         * e / editor
+        * s / spectator
+        * x / export
+        * n / generate
         Select the program mode.
+    -O, --output <string>
+        Directory for the exported pictures. (default: .)
+    -f, --format <string>
+        * png (default)
+        * svg
+        Picture format for the export.
+    --seed <integer>
+        Generator: seed of the first cave to try, 0 to 255. (default: random)
+    --cave <integer>x<integer>
+        Generator: width and height of the cave. (default: 40x22)
+    --fill <char>=<integer>
+        Generator: chance of an object out of 256, later ones win. Can be used multiple times.
+        (default: ' '=60 O=45 +=15)
+    --gems <integer>
+        Generator: gems to collect, at least as many have to be reachable from the start.
+        (default: 10)
     -s, --size <integer>
-        Object size for GUI. (default 30 pixels).
+        Object size for the GUI and the export. (default 30 pixels).
     -d, --delay <integer>
-        Delay between frames. (default: 1000 ms)\
+        Delay between frames. (default: 1000 ms)
+    -S, --stats <string>
+        Write the statistics of every run to a JSON file.
+    --scores <string>
+        File for the high scores. (default: boulder_dash/scores in the user data directory)
+    -o, --objects <string>
+        Load custom objects from a definitions file.
+    -R, --record <string>
+        Record the drawn frames, as a GIF in the GUI and as an asciicast otherwise.
+    -a, --address <string>
+        Game: stream the game to spectators on this address.
+        Spectator: address of the game to watch.\
 ";
 
 #[derive(Debug, PartialEq, Eq)]
@@ -49,6 +90,9 @@ impl FromStr for InteractionMode {
 pub enum ProgramMode {
     Game,
     Editor,
+    Spectator,
+    Export,
+    Generator,
 }
 
 impl FromStr for ProgramMode {
@@ -57,17 +101,52 @@ impl FromStr for ProgramMode {
         match s.to_lowercase().as_str() {
             "g" | "b" | "game" => Ok(Self::Game),
             "e" | "editor" => Ok(Self::Editor),
+            "s" | "spectator" => Ok(Self::Spectator),
+            "x" | "export" => Ok(Self::Export),
+            "n" | "generate" => Ok(Self::Generator),
             _ => Err(format!("Can't parse `{s}` as a valid program mode!")),
         }
     }
 }
 
+#[derive(Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    Png,
+    Svg,
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "png" => Ok(Self::Png),
+            "svg" => Ok(Self::Svg),
+            _ => Err(format!("Can't parse `{s}` as a valid export format!")),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub struct Arguments {
     pub size: u32,
     pub pause: bool,
+    pub resume: bool,
+    pub demo: bool,
+    pub debug: bool,
     pub delay: Duration,
+    pub address: Option<String>,
+    pub objects_path: Option<String>,
+    pub stats_path: Option<String>,
+    pub scores_path: Option<String>,
+    pub record_path: Option<String>,
     pub level_paths: Vec<String>,
+    pub output_dir: String,
+    pub export_format: ExportFormat,
+    pub seed: Option<u8>,
+    pub cave_size: (usize, usize),
+    pub fill: Vec<(char, u8)>, // empty for the default densities
+    pub gems: usize,
+    pub walled: bool,
     pub program_mode: ProgramMode,
     pub interaction_mode: InteractionMode,
 }
@@ -77,14 +156,41 @@ impl Default for Arguments {
         Self {
             size: 30,
             pause: false,
+            resume: false,
+            demo: false,
+            debug: false,
             delay: Duration::from_millis(1000),
+            address: None,
+            objects_path: None,
+            stats_path: None,
+            scores_path: None,
+            record_path: None,
             level_paths: vec![],
+            output_dir: ".".to_string(),
+            export_format: ExportFormat::Png,
+            seed: None,
+            cave_size: (40, 22),
+            fill: vec![],
+            gems: 10,
+            walled: true,
             program_mode: ProgramMode::Game,
             interaction_mode: InteractionMode::Tui,
         }
     }
 }
 
+// <char>=<probability>
+fn parse_fill(arg_opt: Option<String>) -> Result<(char, u8), String> {
+    let value = arg_opt.ok_or("Missing value for `--fill`!")?;
+    let error = || format!("Can't parse `{value}` as `<char>=<0-255>`!");
+
+    let mut chars = value.chars();
+    match (chars.next(), chars.next()) {
+        (Some(object), Some('=')) => Ok((object, chars.as_str().parse().map_err(|_| error())?)),
+        _ => Err(error()),
+    }
+}
+
 fn parse_arg<T, E>(arg_opt: Option<String>, arg_name: &str) -> Result<T, String>
 where
     T: FromStr<Err = E>,
@@ -107,14 +213,46 @@ impl Arguments {
                     std::process::exit(0);
                 }
                 "-p" | "--pause" => config.pause = true,
+                "-c" | "--continue" => config.resume = true,
+                "-D" | "--demo" => config.demo = true,
+                "--debug" => config.debug = true,
 
                 "-s" | "--size" => config.size = parse_arg(args.next(), arg.as_str())?,
                 "-d" | "--delay" => {
                     config.delay = Duration::from_millis(parse_arg(args.next(), arg.as_str())?);
                 }
+                "-a" | "--address" => config.address = Some(parse_arg(args.next(), arg.as_str())?),
+                "-S" | "--stats" => {
+                    config.stats_path = Some(parse_arg(args.next(), arg.as_str())?);
+                }
+                "--scores" => {
+                    config.scores_path = Some(parse_arg(args.next(), arg.as_str())?);
+                }
+                "-R" | "--record" => {
+                    config.record_path = Some(parse_arg(args.next(), arg.as_str())?);
+                }
+                "-o" | "--objects" => {
+                    config.objects_path = Some(parse_arg(args.next(), arg.as_str())?);
+                }
                 "-l" | "--level" => config
                     .level_paths
                     .push(parse_arg(args.next(), arg.as_str())?),
+                "-O" | "--output" => config.output_dir = parse_arg(args.next(), arg.as_str())?,
+                "-f" | "--format" => {
+                    config.export_format = parse_arg(args.next(), arg.as_str())?;
+                }
+                "--seed" => config.seed = Some(parse_arg(args.next(), arg.as_str())?),
+                "--cave" => {
+                    let value: String = parse_arg(args.next(), arg.as_str())?;
+                    config.cave_size = value
+                        .split_once('x')
+                        .and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)))
+                        .filter(|&(w, h)| w > 2 && h > 2)
+                        .ok_or_else(|| format!("Can't parse `{value}` as a cave size!"))?;
+                }
+                "--fill" => config.fill.push(parse_fill(args.next())?),
+                "--gems" => config.gems = parse_arg(args.next(), arg.as_str())?,
+                "--open" => config.walled = false,
                 "-r" | "--run" => config.program_mode = parse_arg(args.next(), arg.as_str())?,
                 "-m" | "--mode" => config.interaction_mode = parse_arg(args.next(), arg.as_str())?,
 
@@ -122,9 +260,15 @@ impl Arguments {
             }
         }
 
-        match config.level_paths.first() {
-            Some(_) => Ok(config),
-            None => Err("Specify a level path with `-l some/path`!".into()),
+        match config.program_mode {
+            ProgramMode::Spectator if config.address.is_none() => {
+                Err("Specify a game address with `-a host:port`!".into())
+            }
+            ProgramMode::Spectator => Ok(config),
+            _ if config.level_paths.is_empty() => {
+                Err("Specify a level path with `-l some/path`!".into())
+            }
+            _ => Ok(config),
         }
     }
 }
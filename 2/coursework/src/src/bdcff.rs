@@ -0,0 +1,305 @@
+// Boulder Dash Common File Format, see https://www.boulder-dash.nl/bdcff/
+use crate::{levels::Metadata, random::Random};
+
+// BDCFF map characters and object names to the native characters
+const ELEMENTS: [(char, &str, char); 16] = [
+    (' ', "SPACE", ' '),
+    ('.', "DIRT", '*'),
+    ('r', "BOULDER", 'O'),
+    ('d', "DIAMOND", '+'),
+    ('W', "STEELWALL", '%'),
+    ('w', "WALL", '#'),
+    ('m', "MAGICWALL", '#'),
+    ('x', "EXPANDINGWALL", '~'),
+    ('X', "OUTBOX", 'X'),
+    ('H', "HIDDENOUTBOX", 'X'),
+    ('q', "FIREFLYl", 'q'),
+    ('Q', "FIREFLYu", 'q'),
+    ('o', "FIREFLYr", 'q'),
+    ('O', "FIREFLYd", 'q'),
+    ('P', "INBOX", 'p'),
+    ('P', "PLAYER", 'p'),
+];
+const UNKNOWN: char = '?';
+
+fn from_map_char(chr: char) -> char {
+    ELEMENTS
+        .iter()
+        .find(|(map_chr, ..)| *map_chr == chr)
+        .map_or(UNKNOWN, |(.., native)| *native)
+}
+
+fn from_name(name: &str) -> char {
+    ELEMENTS
+        .iter()
+        .find(|(_, element, _)| name.eq_ignore_ascii_case(element))
+        .map_or(UNKNOWN, |(.., native)| *native)
+}
+
+pub fn is_bdcff(contents: &str) -> bool {
+    contents.trim_start().starts_with("[BDCFF]")
+}
+
+fn rectangle(x1: usize, y1: usize, x2: usize, y2: usize) -> impl Iterator<Item = (usize, usize)> {
+    (y1.min(y2)..=y1.max(y2)).flat_map(move |y| (x1.min(x2)..=x1.max(x2)).map(move |x| (x, y)))
+}
+
+#[derive(Default)]
+struct Cave {
+    size: (usize, usize),
+    metadata: Metadata,
+    seed: Option<u8>,
+    initial_fill: Option<char>,
+    random_fill: Vec<(char, u8)>,
+    map: Vec<Vec<char>>,
+    objects: Vec<String>,
+}
+
+impl Cave {
+    fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let mut values = value.split_whitespace();
+        // Several values are given for the difficulty levels, only the first one is used
+        let mut number = || -> Result<usize, String> {
+            values
+                .next()
+                .and_then(|v| v.parse().ok())
+                .ok_or_else(|| format!("Can't parse `{value}` as `{key}`!"))
+        };
+
+        match key.to_lowercase().as_str() {
+            "name" => self.metadata.title = Some(value.trim().to_string()),
+            "size" => self.size = (number()?, number()?),
+            "diamondsrequired" => self.metadata.quota = Some(number()?),
+            // Seconds in the original, a tick takes a second only at the default delay,
+            // which can be changed while playing, so the number is kept as ticks
+            "cavetime" => self.metadata.time = Some(number()?),
+            "diamondvalue" => {
+                self.metadata.gem_points = Some(number()?);
+                // The second value is for the gems past the quota
+                self.metadata.extra_points = number().ok();
+            }
+            "randomseed" => {
+                // -1 means no random fill
+                self.seed = value.split_whitespace().next().and_then(|v| v.parse().ok());
+            }
+            "initialfill" => self.initial_fill = Some(from_name(value.trim())),
+            "randomfill" => {
+                let fields: Vec<_> = value.split_whitespace().collect();
+                for pair in fields.chunks(2) {
+                    let [name, probability] = pair else {
+                        return Err(format!("Can't parse `{value}` as `{key}`!"));
+                    };
+                    let probability = probability
+                        .parse()
+                        .map_err(|_| format!("Can't parse `{value}` as `{key}`!"))?;
+                    self.random_fill.push((from_name(name), probability));
+                }
+            }
+            _ => (),
+        }
+
+        Ok(())
+    }
+
+    fn fill(&self) -> Vec<Vec<char>> {
+        let (width, height) = self.size;
+        let mut matrix = vec![vec![self.initial_fill.unwrap_or('*'); width]; height];
+
+        if let Some(seed) = self.seed {
+            let mut random = Random::new(seed);
+            for row in matrix.iter_mut().take(height.saturating_sub(1)).skip(1) {
+                for cell in row.iter_mut() {
+                    let value = random.next();
+                    for &(object, probability) in &self.random_fill {
+                        if value < probability {
+                            *cell = object;
+                        }
+                    }
+                }
+            }
+        }
+
+        // Steel border
+        for (y, row) in matrix.iter_mut().enumerate() {
+            for (x, cell) in row.iter_mut().enumerate() {
+                if x == 0 || y == 0 || x + 1 == width || y + 1 == height {
+                    *cell = '%';
+                }
+            }
+        }
+
+        matrix
+    }
+
+    fn draw(matrix: &mut [Vec<char>], command: &str) -> Result<(), String> {
+        let Some((kind, args)) = command.split_once('=') else {
+            return Err(format!("Can't parse the object `{command}`!"));
+        };
+
+        let args: Vec<_> = args.split_whitespace().collect();
+        let coordinates: Vec<usize> = args.iter().map_while(|a| a.parse().ok()).collect();
+        let objects: Vec<char> = args[coordinates.len()..]
+            .iter()
+            .map(|name| from_name(name))
+            .collect();
+
+        let mut set = |x: usize, y: usize, object: char| {
+            if let Some(cell) = matrix.get_mut(y).and_then(|row| row.get_mut(x)) {
+                *cell = object;
+            }
+        };
+
+        match (kind.to_lowercase().as_str(), &coordinates[..], &objects[..]) {
+            ("point", &[x, y], &[object]) => set(x, y, object),
+            ("line", &[x1, y1, x2, y2], &[object]) => {
+                let steps = x1.abs_diff(x2).max(y1.abs_diff(y2));
+                for step in 0..=steps {
+                    let along = |from: usize, to: usize| match steps {
+                        0 => from,
+                        _ if to >= from => from + (to - from) * step / steps,
+                        _ => from - (from - to) * step / steps,
+                    };
+                    set(along(x1, x2), along(y1, y2), object);
+                }
+            }
+            ("rectangle", &[x1, y1, x2, y2], &[object]) => {
+                for (x, y) in rectangle(x1, y1, x2, y2) {
+                    if x == x1 || x == x2 || y == y1 || y == y2 {
+                        set(x, y, object);
+                    }
+                }
+            }
+            ("fillrect", &[x1, y1, x2, y2], &[object, ..]) => {
+                let fill = objects.get(1).copied().unwrap_or(object);
+                for (x, y) in rectangle(x1, y1, x2, y2) {
+                    let border = x == x1 || x == x2 || y == y1 || y == y2;
+                    set(x, y, if border { object } else { fill });
+                }
+            }
+            // Other drawing commands aren't supported
+            _ => (),
+        }
+
+        Ok(())
+    }
+
+    fn to_native(&self) -> Result<String, String> {
+        let mut matrix = if self.map.is_empty() {
+            self.fill()
+        } else {
+            self.map.clone()
+        };
+        for command in &self.objects {
+            Self::draw(&mut matrix, command)?;
+        }
+
+        let mut contents = self.metadata.to_string();
+        for row in matrix {
+            contents.extend(row);
+            contents.push('\n');
+        }
+        Ok(contents)
+    }
+}
+
+// Every cave in the file as a level in the native format
+pub fn convert(contents: &str) -> Result<Vec<String>, String> {
+    let mut levels = vec![];
+    let mut cave: Option<Cave> = None;
+    let mut section = String::new();
+
+    for line in contents.lines().map(|l| l.trim_end_matches('\r')) {
+        let trimmed = line.trim();
+
+        if trimmed.starts_with('[') && trimmed.ends_with(']') {
+            let name = trimmed[1..trimmed.len() - 1].to_lowercase();
+            match name.as_str() {
+                "cave" => {
+                    cave = Some(Cave {
+                        size: (40, 22),
+                        ..Default::default()
+                    });
+                }
+                "/cave" => {
+                    let finished = cave.take().ok_or("Unexpected `[/cave]`!")?;
+                    levels.push(finished.to_native()?);
+                }
+                _ => (),
+            }
+            // The cave keys can follow the map and the objects
+            section = match name.as_str() {
+                "/map" | "/objects" => "cave".to_string(),
+                _ => name,
+            };
+            continue;
+        }
+
+        let Some(cave) = cave.as_mut() else {
+            continue;
+        };
+        match section.as_str() {
+            // The map keeps the spaces
+            "map" => cave.map.push(line.chars().map(from_map_char).collect()),
+            "objects" if !trimmed.is_empty() => cave.objects.push(trimmed.to_string()),
+            "cave" => {
+                if let Some((key, value)) = trimmed.split_once('=') {
+                    cave.set(key.trim(), value)?;
+                }
+            }
+            _ => (),
+        }
+    }
+
+    if levels.is_empty() {
+        return Err("No caves found!".into());
+    }
+    Ok(levels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn elements() {
+        assert_eq!(from_map_char('d'), '+');
+        assert_eq!(from_map_char('X'), 'X');
+        assert_eq!(from_map_char('~'), UNKNOWN);
+        assert_eq!(from_name("boulder"), 'O');
+        assert_eq!(from_name("STEELWALL"), '%');
+        assert_eq!(from_name("AMOEBA"), UNKNOWN);
+    }
+
+    // The same seed always fills the cave the same way, the border is steel
+    #[test]
+    fn random_fill() {
+        let cave = "[BDCFF]\n[cave]\nName=Fill\nSize=5 4\nRandomSeed=3\n\
+            InitialFill=SPACE\nRandomFill=BOULDER 100 DIAMOND 40\nDiamondValue=2 5\n[/cave]\n";
+        assert_eq!(
+            convert(cave).unwrap(),
+            ["@level Fill\n@gem_points 2\n@extra_points 5\n%%%%%\n% +O%\n% O %\n%%%%%\n"]
+        );
+    }
+
+    #[test]
+    fn objects_and_map() {
+        let cave = "[BDCFF]\n[cave]\nSize=4 3\n[map]\nWWWW\nWP.W\nWWWW\n[/map]\n\
+            [objects]\nPoint=2 1 DIAMOND\n[/objects]\nDiamondsRequired=1\nCaveTime=50\n[/cave]\n";
+        assert_eq!(
+            convert(cave).unwrap(),
+            ["@quota 1\n@time 50\n%%%%\n%p+%\n%%%%\n"]
+        );
+    }
+
+    #[test]
+    fn several_caves() {
+        let caves = "[BDCFF]\n[game]\nName=Pack\n[/game]\n\
+            [cave]\nName=A\n[map]\nWPdW\n[/map]\n[/cave]\n\
+            [cave]\nName=B\n[map]\nWdPW\n[/map]\n[/cave]\n";
+        assert_eq!(
+            convert(caves).unwrap(),
+            ["@level A\n%p+%\n", "@level B\n%+p%\n"]
+        );
+        assert!(convert("[BDCFF]\n").is_err());
+    }
+}
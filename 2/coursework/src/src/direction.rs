@@ -1,6 +1,7 @@
 use crate::interaction::Input;
+use std::{fmt, str::FromStr};
 
-#[derive(PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Direction {
     Up,
     Down,
@@ -23,7 +24,87 @@ impl TryFrom<Input> for Direction {
     }
 }
 
+impl FromStr for Direction {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "up" => Ok(Self::Up),
+            "down" => Ok(Self::Down),
+            "left" => Ok(Self::Left),
+            "right" => Ok(Self::Right),
+            _ => Err(format!("Can't parse `{s}` as a direction!")),
+        }
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", format!("{self:?}").to_lowercase())
+    }
+}
+
+// What a player does in a direction, snapping takes the object there without moving
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Move {
+    Step(Direction),
+    Snap(Direction),
+}
+
+impl From<Direction> for Move {
+    fn from(direction: Direction) -> Self {
+        Self::Step(direction)
+    }
+}
+
+impl TryFrom<Input> for Move {
+    type Error = ();
+
+    fn try_from(input: Input) -> Result<Self, Self::Error> {
+        match input {
+            Input::Snap(input) => Direction::try_from(*input).map(Self::Snap),
+            input => Direction::try_from(input).map(Self::Step),
+        }
+    }
+}
+
 impl Direction {
+    pub fn between(from: &(usize, usize), to: &(usize, usize)) -> Option<Self> {
+        [Self::Up, Self::Down, Self::Left, Self::Right]
+            .into_iter()
+            .find(|direction| from != to && direction.apply_to(from) == *to)
+    }
+
+    pub const fn opposite(&self) -> Self {
+        match self {
+            Self::Up => Self::Down,
+            Self::Down => Self::Up,
+            Self::Left => Self::Right,
+            Self::Right => Self::Left,
+        }
+    }
+
+    // A quarter turn counter-clockwise on the screen
+    pub const fn left(&self) -> Self {
+        match self {
+            Self::Up => Self::Left,
+            Self::Left => Self::Down,
+            Self::Down => Self::Right,
+            Self::Right => Self::Up,
+        }
+    }
+
+    pub const fn right(&self) -> Self {
+        self.left().opposite()
+    }
+
+    // The sides when facing this direction, left-hand one first
+    pub const fn across(&self) -> [Self; 2] {
+        match self {
+            Self::Up | Self::Down => [Self::Left, Self::Right],
+            Self::Left | Self::Right => [Self::Up, Self::Down],
+        }
+    }
+
     pub const fn apply_to(&self, point: &(usize, usize)) -> (usize, usize) {
         let (x, y) = match self {
             Self::Up => (0, -1),
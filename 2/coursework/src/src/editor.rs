@@ -1,20 +1,75 @@
 use crate::{
     args::Arguments,
+    bdcff,
     direction::Direction,
+    grid::Grid,
     interaction::{Drawable, Input, Interaction, Mode},
+    levels::{self, Metadata},
+    menu::{Choice, Menu},
     objects::{Labels, Object},
+    watcher::{Watcher, POLL_INTERVAL},
     Point,
 };
-use std::{collections::HashSet, error::Error, fs, io, thread, time::Duration};
+use std::{
+    cmp,
+    collections::HashSet,
+    error::Error,
+    fs::{self, File},
+    io::{self, Write},
+    path::Path,
+    slice,
+    time::{Duration, Instant},
+};
+
+const BACKUPS: usize = 3;
+const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+// The file is replaced in one step, a crash leaves either the old or the new version
+fn write_atomic(path: &str, contents: &str) -> io::Result<()> {
+    let tmp_path = format!("{path}.tmp");
+    let mut file = File::create(&tmp_path)?;
+    file.write_all(contents.as_bytes())?;
+    // The contents have to be on the disk before the rename, and the rename after it
+    file.sync_all()?;
+    fs::rename(tmp_path, path)?;
+
+    // Directories can only be opened like this on Unix
+    #[cfg(unix)]
+    {
+        let dir = Path::new(path).parent();
+        let dir = dir.filter(|dir| !dir.as_os_str().is_empty());
+        File::open(dir.unwrap_or(Path::new(".")))?.sync_all()?;
+    }
+    Ok(())
+}
+
+// `<path>.bak.1` is the latest previous version, the oldest one is dropped
+fn back_up(path: &str) -> io::Result<()> {
+    let backup = |n: usize| format!("{path}.bak.{n}");
+    for n in (1..BACKUPS).rev() {
+        if Path::new(&backup(n)).exists() {
+            fs::rename(backup(n), backup(n + 1))?;
+        }
+    }
+    fs::copy(path, backup(1)).map(|_| ())
+}
 
 #[derive(Default)]
 pub struct Editor {
     file_name: String,
+    save_path: String, // differs from the file name for the imported levels
+    page: usize,
+    pages: Vec<(Metadata, Grid)>, // the current page is taken out into the fields below
+    metadata: Metadata,
     cursor: Point,
     pen_down: bool,
+    modified: bool,                // unsaved edits
+    autosaved_at: Option<Instant>, // None until the first autosave after a save
+    notice: String,
+    watcher: Watcher,
     current_object: usize,
     damaged: HashSet<Point>,
-    matrix: Vec<Vec<Object>>,
+    matrix: Grid,
 }
 
 impl Drawable for Editor {
@@ -25,12 +80,9 @@ impl Drawable for Editor {
     fn get_damaged(&mut self) -> Vec<Point> {
         std::mem::take(&mut self.damaged).into_iter().collect()
     }
-    fn get_objects(&self) -> &Vec<Vec<Object>> {
+    fn get_objects(&self) -> &Grid {
         &self.matrix
     }
-    fn get_object(&self, (x, y): Point) -> Option<&Object> {
-        self.matrix.get(y)?.get(x)
-    }
 
     fn get_status(&self) -> String {
         let (x, y) = self.cursor;
@@ -42,59 +94,238 @@ impl Drawable for Editor {
         objects[self.current_object].push(']');
         let pen = if self.pen_down { "down" } else { "up" };
 
-        format!("Pen {pen}\nCursor pos: ({x}, {y})\n{}", objects.join(" "))
+        let mut level = vec![];
+        if self.pages.len() > 1 {
+            level.push(format!("Level {}/{}", self.page + 1, self.pages.len()));
+        }
+        level.extend(self.metadata.title.clone());
+        let mut level = level.join(": ");
+        if !level.is_empty() {
+            level.push('\n');
+        }
+
+        format!(
+            "File: {}{}\n{level}Pen {pen}\nCursor pos: ({x}, {y})\n{}{}",
+            self.save_path,
+            if self.modified { " (modified)" } else { "" },
+            objects.join(" "),
+            self.notice,
+        )
     }
 }
 
 impl Editor {
-    pub fn new(args: &Arguments) -> io::Result<Self> {
+    pub fn new(args: &Arguments) -> Result<Self, Box<dyn Error>> {
+        let file_name = args.level_paths[0].clone();
+        // Imported levels are saved next to the original in the native format
+        let save_path = if bdcff::is_bdcff(&fs::read_to_string(&file_name)?) {
+            Path::new(&file_name)
+                .with_extension("level")
+                .to_string_lossy()
+                .into_owned()
+        } else {
+            file_name.clone()
+        };
+
         let mut editor = Self {
-            file_name: args.level_paths[0].clone(),
+            watcher: Watcher::new(slice::from_ref(&file_name)),
+            file_name,
+            save_path,
             ..Default::default()
         };
         editor.reload()?;
         Ok(editor)
     }
 
-    fn reload(&mut self) -> io::Result<()> {
-        self.matrix = vec![];
+    fn parse_page(contents: &str) -> Result<(Metadata, Grid), String> {
+        let (metadata, contents) = Metadata::parse(contents)?;
+        let mut rows: Vec<Vec<Object>> = contents
+            .trim()
+            .lines()
+            .map(|line| line.trim().chars().map(Object::new).collect())
+            .collect();
 
-        let contents = fs::read_to_string(&self.file_name)?;
-        for (y, line) in contents.trim().lines().map(str::trim).enumerate() {
-            self.matrix.push(line.chars().map(Object::new).collect());
-            self.damaged.extend((0..line.len()).map(|x| (x, y)));
+        if rows.is_empty() {
+            rows.push(vec![Object::default()]);
         }
 
-        if self.matrix.is_empty() {
-            self.matrix.push(vec![Object::default()]);
-            self.damaged.insert((0, 0));
-        }
+        Ok((metadata, Grid::new(rows)))
+    }
+
+    fn get_recovery_path(&self) -> String {
+        format!("{}.recovery", self.save_path)
+    }
+
+    fn reload(&mut self) -> Result<(), Box<dyn Error>> {
+        self.load(&self.file_name.clone())
+    }
+
+    fn load(&mut self, path: &str) -> Result<(), Box<dyn Error>> {
+        self.pages = levels::read(path)?
+            .iter()
+            .map(|contents| Self::parse_page(contents))
+            .collect::<Result<_, _>>()?;
+
+        self.page = self.page.min(self.pages.len() - 1);
+        (self.metadata, self.matrix) = std::mem::take(&mut self.pages[self.page]);
+        self.modified = false;
+        self.damage_all();
 
         Ok(())
     }
 
-    fn save(&mut self) -> io::Result<()> {
+    fn damage_all(&mut self) {
+        self.damaged.extend(self.matrix.points());
+    }
+
+    // Going past the last page adds a new one, it isn't saved until something is drawn on it
+    fn switch_page(&mut self, page: usize) {
+        self.pages[self.page] = (
+            std::mem::take(&mut self.metadata),
+            std::mem::take(&mut self.matrix),
+        );
+
+        if page == self.pages.len() {
+            let metadata = Metadata {
+                title: Some(format!("Level {}", page + 1)),
+                ..Default::default()
+            };
+            self.pages
+                .push((metadata, Grid::new(vec![vec![Object::default()]])));
+        }
+
+        self.page = page;
+        (self.metadata, self.matrix) = std::mem::take(&mut self.pages[page]);
+        self.cursor = (0, 0);
+        self.damage_all();
+    }
+
+    fn get_contents(&self) -> String {
         let mut contents = String::new();
 
-        for row in &self.matrix {
-            contents += row.iter().map(Labels::char).collect::<String>().trim();
-            contents.push('\n');
+        for (idx, page) in self.pages.iter().enumerate() {
+            let (metadata, matrix) = if idx == self.page {
+                (&self.metadata, &self.matrix)
+            } else {
+                (&page.0, &page.1)
+            };
+
+            let empty = matrix.rows().flatten().all(|obj| obj == &Object::default());
+            if self.pages.len() > 1 && empty {
+                continue;
+            }
+
+            // Levels in a pack are separated by the titles
+            let mut metadata = metadata.clone();
+            if self.pages.len() > 1 && metadata.title.is_none() {
+                metadata.title = Some(format!("Level {}", idx + 1));
+            }
+
+            contents += &metadata.to_string();
+            for row in matrix.rows() {
+                contents += row.iter().map(Labels::char).collect::<String>().trim();
+                contents.push('\n');
+            }
+        }
+
+        contents.trim_end().to_string()
+    }
+
+    fn save(&mut self) -> io::Result<()> {
+        let contents = self.get_contents();
+        // Saving without changes would push the older versions out
+        match fs::read_to_string(&self.save_path) {
+            Ok(old) if old != contents => back_up(&self.save_path)?,
+            Ok(_) => (),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => (),
+            Err(err) => return Err(err),
+        }
+
+        write_atomic(&self.save_path, &contents)?;
+        self.modified = false;
+        self.autosaved_at = None;
+        match fs::remove_file(self.get_recovery_path()) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+            _ => (),
+        }
+        // Our own changes shouldn't trigger a reload
+        if self.save_path == self.file_name {
+            self.watcher.changed();
+        }
+        Ok(())
+    }
+
+    // Edits made here are lost only when asked
+    fn confirm_reload(&mut self, interaction: &mut Mode) -> Result<bool, Box<dyn Error>> {
+        let items = vec!["Reload from disk".into(), "Keep my edits".into()];
+        let mut menu = Menu::new(&format!("{} was changed on disk", self.file_name), items);
+        let choice = menu.run(interaction)?;
+
+        self.damage_all();
+        Ok(matches!(choice, Choice::Select(0)))
+    }
+
+    // Unsaved edits are written aside every once in a while
+    fn autosave(&mut self) -> io::Result<()> {
+        let due = self
+            .autosaved_at
+            .is_none_or(|time| time.elapsed() >= AUTOSAVE_INTERVAL);
+        if self.modified && due {
+            write_atomic(&self.get_recovery_path(), &self.get_contents())?;
+            self.autosaved_at = Some(Instant::now());
         }
+        Ok(())
+    }
 
-        fs::write(&self.file_name, contents.trim())
+    // Left behind by an editor that didn't get to save, false when the user quits
+    fn offer_recovery(&mut self, interaction: &mut Mode) -> Result<bool, Box<dyn Error>> {
+        let path = self.get_recovery_path();
+        if !Path::new(&path).exists() {
+            return Ok(true);
+        }
+
+        let items = vec!["Restore the edits".into(), "Discard them".into()];
+        let mut menu = Menu::new(&format!("Found unsaved edits in {path}"), items);
+        match menu.run(interaction)? {
+            Choice::Select(0) => {
+                self.load(&path)?;
+                self.modified = true;
+                self.autosaved_at = Some(Instant::now());
+            }
+            Choice::Select(_) => fs::remove_file(path)?,
+            Choice::Back => (),
+            Choice::Quit => return Ok(false),
+        }
+
+        self.damage_all();
+        Ok(true)
     }
 
     pub fn run(&mut self, interaction: &mut Mode) -> Result<(), Box<dyn Error>> {
+        if !self.offer_recovery(interaction)? {
+            return Ok(());
+        }
         interaction.draw(self)?;
 
         let objects = Object::get_all_displayable();
 
         loop {
-            thread::sleep(Duration::from_millis(25));
-
             let mut direction = None;
 
-            let input = interaction.get_input();
+            let input = interaction.wait_input(Some(POLL_INTERVAL));
+            self.autosave()?;
+
+            if !self.watcher.changed().is_empty() {
+                if !self.modified || self.confirm_reload(interaction)? {
+                    // A half written file is reported, the next change is picked up again
+                    self.notice = match self.reload() {
+                        Ok(()) => String::new(),
+                        Err(err) => format!("\n{err}"),
+                    };
+                }
+                interaction.draw(self)?;
+            }
+
             match input {
                 Input::Quit | Input::Q => {
                     self.save()?;
@@ -120,6 +351,12 @@ impl Editor {
                         self.current_object = 0;
                     }
                 }
+                Input::PageUp => {
+                    if self.page > 0 {
+                        self.switch_page(self.page - 1);
+                    }
+                }
+                Input::PageDown => self.switch_page(self.page + 1),
 
                 Input::Up
                 | Input::Down
@@ -130,29 +367,31 @@ impl Editor {
                 | Input::S
                 | Input::D => direction = Direction::try_from(input).ok(),
 
-                Input::Unknown => continue,
+                Input::E
+                | Input::I
+                | Input::J
+                | Input::K
+                | Input::L
+                | Input::N
+                | Input::Snap(_)
+                | Input::Enter
+                | Input::Unknown => continue,
             }
 
             if let Some(dir) = direction {
+                // Empty rows and columns are trimmed when moving back
+                let (width, height) = (self.matrix.get_width(), self.matrix.get_height());
+                let void = |(x, y): Point| self.matrix.get_or_border((x, y)) == &Object::default();
                 match dir {
-                    Direction::Up => {
-                        if self.matrix.len() > 1
-                            && self
-                                .matrix
-                                .last()
-                                .map_or(false, |l| l.iter().all(|o| *o == Object::default()))
-                        {
-                            self.matrix.pop();
-                        }
+                    Direction::Up if height > 1 && (0..width).all(|x| void((x, height - 1))) => {
+                        self.matrix.resize(width, height - 1);
                     }
                     Direction::Left => {
-                        for row in &mut self.matrix {
-                            while self.cursor.0 < row.len()
-                                && *row.last().unwrap() == Object::default()
-                            {
-                                row.pop();
-                            }
+                        let mut width = width;
+                        while self.cursor.0 < width && (0..height).all(|y| void((width - 1, y))) {
+                            width -= 1;
                         }
+                        self.matrix.resize(width, height);
                     }
                     _ => (),
                 }
@@ -162,19 +401,22 @@ impl Editor {
             }
 
             let (x, y) = self.cursor;
-
-            while y + 1 > self.matrix.len() {
-                self.matrix.push(vec![]);
-            }
-            // Moving up or down on a shorter row
-            while x + 1 > self.matrix[y].len() {
-                self.matrix[y].push(Object::default());
-                self.damaged.insert((self.matrix[y].len() - 1, y));
+            if !self.matrix.contains((x, y)) {
+                let (width, height) = (self.matrix.get_width(), self.matrix.get_height());
+                self.matrix
+                    .resize(cmp::max(width, x + 1), cmp::max(height, y + 1));
+                self.damaged.extend(
+                    self.matrix
+                        .points()
+                        .filter(|&(x, y)| x >= width || y >= height),
+                );
             }
 
-            if self.pen_down {
-                self.matrix[self.cursor.1][self.cursor.0] = objects[self.current_object].clone();
+            let object = &objects[self.current_object];
+            if self.pen_down && self.matrix.get(self.cursor) != Some(object) {
+                self.matrix.set(self.cursor, object.clone());
                 self.damaged.insert(self.cursor);
+                self.modified = true;
             }
 
             interaction.draw(self)?;
@@ -0,0 +1,17 @@
+// The simulation without any interaction, for bots and external tools:
+//
+// let mut level = Level::new(&fs::read_to_string("assets/levels/level")?)?;
+// let mut search = level.clone();
+// search.step(Some(Direction::Down.into()));
+// let (grid, score, state) = (search.get_objects(), search.get_score(), search.get_state());
+pub use crate::{
+    direction::{Direction, Move},
+    export::{render_png, render_svg},
+    game::{
+        level::{Level, State},
+        stats::Stats,
+    },
+    grid::Grid,
+    objects::{registry::load as load_objects, Labels, Object, Properties},
+    Point,
+};
@@ -0,0 +1,203 @@
+use crate::{
+    args::{Arguments, ExportFormat},
+    game::level::Level,
+    grid::Grid,
+    levels,
+    objects::Labels,
+};
+use sdl2::{
+    image::{LoadSurface, SaveSurface},
+    pixels::{Color, PixelFormatEnum},
+    rect::Rect,
+    surface::Surface,
+};
+use std::{collections::HashMap, error::Error, fs, io, path::Path};
+
+const SPRITES_DIR: &str = "assets/sprites";
+
+// Missing sprites are drawn as unknown objects, like in the GUI
+fn sprite_path(sprite: &str) -> String {
+    let path = format!("{SPRITES_DIR}/{sprite}");
+    if Path::new(&path).exists() {
+        path
+    } else {
+        format!("{SPRITES_DIR}/unknown")
+    }
+}
+
+// A cell sized sprite on the black background, only the image part of SDL is used
+pub fn load_sprite(sprite: &str, scale: u32) -> Result<Surface<'static>, String> {
+    let mut tile = Surface::new(scale, scale, PixelFormatEnum::RGBA32)?;
+    tile.fill_rect(None, Color::BLACK)?;
+    Surface::from_file(sprite_path(sprite))?.blit_scaled(None, &mut tile, None)?;
+    Ok(tile)
+}
+
+// No window or video context is needed
+pub fn render_png(grid: &Grid, scale: u32, path: &str) -> Result<(), Box<dyn Error>> {
+    let mut canvas = Surface::new(
+        u32::try_from(grid.get_width())? * scale,
+        u32::try_from(grid.get_height())? * scale,
+        PixelFormatEnum::RGBA32,
+    )?;
+    canvas.fill_rect(None, Color::BLACK)?;
+
+    let mut sprites = HashMap::new();
+    for (x, y) in grid.points() {
+        let sprite = grid.get_or_border((x, y)).sprite();
+        if !sprites.contains_key(&sprite) {
+            sprites.insert(sprite.clone(), load_sprite(&sprite, scale)?);
+        }
+
+        let rect = Rect::new(
+            i32::try_from(x)? * i32::try_from(scale)?,
+            i32::try_from(y)? * i32::try_from(scale)?,
+            scale,
+            scale,
+        );
+        sprites[&sprite].blit(None, &mut canvas, rect)?;
+    }
+
+    Ok(canvas.save(path)?)
+}
+
+// The sprites are embedded, the picture is laid out in cells and scaled by the viewer
+pub fn render_svg(grid: &Grid, scale: u32) -> io::Result<String> {
+    let (width, height) = (grid.get_width(), grid.get_height());
+    let mut defs = String::new();
+    let mut cells = String::new();
+    let mut sprites = HashMap::new();
+
+    for (x, y) in grid.points() {
+        let sprite = grid.get_or_border((x, y)).sprite();
+        if !sprites.contains_key(&sprite) {
+            let id = format!("sprite{}", sprites.len());
+            let data = base64(&fs::read(sprite_path(&sprite))?);
+            defs += &format!(
+                "<image id=\"{id}\" width=\"1\" height=\"1\" href=\"data:image/png;base64,{data}\"/>\n"
+            );
+            sprites.insert(sprite.clone(), id);
+        }
+        cells += &format!(
+            "<use href=\"#{}\" x=\"{x}\" y=\"{y}\"/>\n",
+            sprites[&sprite]
+        );
+    }
+
+    Ok(format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" \
+         viewBox=\"0 0 {width} {height}\" style=\"image-rendering: pixelated\">\n\
+         <rect width=\"{width}\" height=\"{height}\" fill=\"black\"/>\n\
+         <defs>\n{defs}</defs>\n{cells}</svg>\n",
+        width * scale as usize,
+        height * scale as usize,
+    ))
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::new();
+    for chunk in bytes.chunks(3) {
+        let triple = chunk
+            .iter()
+            .enumerate()
+            .fold(0, |acc, (i, &b)| acc | u32::from(b) << (16 - 8 * i));
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(triple >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+
+    encoded
+}
+
+// Every level of every pack goes to its own file in the output directory
+pub fn run(args: &Arguments) -> Result<(), Box<dyn Error>> {
+    fs::create_dir_all(&args.output_dir)?;
+
+    for path in &args.level_paths {
+        let pack = levels::read(path)?;
+        let stem = Path::new(path)
+            .file_stem()
+            .map_or_else(|| "level".into(), |s| s.to_string_lossy());
+
+        for (idx, contents) in pack.iter().enumerate() {
+            let level = Level::new(contents).map_err(|e| format!("{path}: {e}"))?;
+            let name = if pack.len() > 1 {
+                format!("{stem}-{}", idx + 1)
+            } else {
+                stem.to_string()
+            };
+
+            let output = match args.export_format {
+                ExportFormat::Png => {
+                    let output = format!("{}/{name}.png", args.output_dir);
+                    render_png(level.get_objects(), args.size, &output)?;
+                    output
+                }
+                ExportFormat::Svg => {
+                    let output = format!("{}/{name}.svg", args.output_dir);
+                    fs::write(&output, render_svg(level.get_objects(), args.size)?)?;
+                    output
+                }
+            };
+            println!("{output}");
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The vectors of RFC 4648, every padding case
+    #[test]
+    fn base64_vectors() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+        assert_eq!(base64(&[0xff, 0xfe, 0xfd]), "//79");
+    }
+
+    fn decode(encoded: &str) -> Vec<u8> {
+        const ALPHABET: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+        let sextets: Vec<u32> = encoded
+            .trim_end_matches('=')
+            .chars()
+            .map(|c| ALPHABET.find(c).unwrap() as u32)
+            .collect();
+
+        let mut bytes = vec![];
+        for chunk in sextets.chunks(4) {
+            let quad = chunk
+                .iter()
+                .enumerate()
+                .fold(0, |acc, (i, &s)| acc | s << (18 - 6 * i));
+            for i in 0..chunk.len() - 1 {
+                bytes.push((quad >> (16 - 8 * i)) as u8);
+            }
+        }
+        bytes
+    }
+
+    #[test]
+    fn base64_round_trip() {
+        for len in 0..=3 {
+            for first in [0, 1, 0x7f, 0x80, 0xff] {
+                let bytes: Vec<u8> = (0..len).map(|i| first ^ (i * 0x55)).collect();
+                let encoded = base64(&bytes);
+                assert_eq!(encoded.len(), len.div_ceil(3) as usize * 4);
+                assert_eq!(decode(&encoded), bytes);
+            }
+        }
+    }
+}
@@ -1,18 +1,26 @@
 use crate::{
     args::Arguments,
-    direction::Direction,
-    interaction::{Drawable, Input, Interaction, Mode},
-    objects::Object,
+    direction::{Direction, Move},
+    grid::Grid,
+    interaction::{Drawable, Input, Interaction, Mode, Motion},
+    levels,
+    scores::{Entry, HighScores},
+    watcher::{Watcher, POLL_INTERVAL},
     Point,
 };
-use std::{
-    error::Error,
-    fs, io, thread,
-    time::{Duration, Instant},
-};
+use std::{cmp, collections::VecDeque, error::Error, fs, io, time::Duration};
 
+mod debug;
 pub mod level;
-use level::{Level, State};
+mod menus;
+pub mod stats;
+use debug::Overlay;
+use level::{Level, Request, State};
+use menus::Action;
+
+const MAX_BUFFERED_DIRECTIONS: usize = 3;
+
+type Source = (String, usize); // file path and the level index in it
 
 #[derive(Default)]
 pub struct Game {
@@ -20,32 +28,104 @@ pub struct Game {
     delay: Duration,
     level_idx: usize,
     levels: Vec<Level>,
+    level_sources: Vec<Source>,
     level_paths: Vec<String>,
+    watcher: Watcher,
+    scores: HighScores,
+    notice: String,
+    stats_path: Option<String>,
+    runs: Vec<String>, // JSON statistics of the finished runs
+    play_time: Duration,
+    redraw_all: bool,           // the level was covered by a menu
+    moves: Vec<(Point, Point)>, // by the last tick, not drawn yet
+    debug: Option<Overlay>,
 }
 
 impl Drawable for Game {
+    fn get_cursor(&self) -> Option<&Point> {
+        self.debug.as_ref().map(|overlay| &overlay.cursor)
+    }
+
     fn get_damaged(&mut self) -> Vec<Point> {
-        self.get_level_mut().get_damaged().into_iter().collect()
+        let damaged = self.get_level_mut().get_damaged();
+        if std::mem::take(&mut self.redraw_all) {
+            return self.get_level().get_objects().points().collect();
+        }
+        damaged.into_iter().collect()
+    }
+    fn get_motion(&mut self) -> Motion {
+        Motion {
+            moves: std::mem::take(&mut self.moves),
+            duration: self.delay,
+        }
     }
-    fn get_objects(&self) -> &Vec<Vec<Object>> {
+    fn get_objects(&self) -> &Grid {
         self.get_level().get_objects()
     }
-    fn get_object(&self, (x, y): Point) -> Option<&Object> {
-        self.get_level().get_objects().get(y)?.get(x)
+    fn get_gravity(&self) -> Direction {
+        self.get_level().get_gravity()
+    }
+    fn get_wrap(&self) -> bool {
+        self.get_level().get_wrap()
     }
 
     fn get_status(&self) -> String {
-        match self.get_level().get_state() {
-            Some(State::Win) => "You have won!".to_string(),
-            Some(State::Lose) => "You have lost!\nR - reload".to_string(),
-            None => format!(
-                "Score: {}/{}\nDelay: {}ms\nPaused: {}",
+        let best = self
+            .scores
+            .get_best(self.get_level().get_hash())
+            .map_or_else(String::new, |entry| format!("\nBest: {entry}"));
+
+        let debug = self
+            .debug
+            .as_ref()
+            .map_or_else(String::new, |overlay| overlay.get_status(self.get_level()));
+
+        let status = match self.get_level().get_state() {
+            Some(State::Win) => format!(
+                "You have won!\nScore: {} in {} ticks{best}{}",
                 self.get_level().get_score(),
-                self.get_level().get_max_score(),
-                self.delay.as_millis(),
-                if self.pause { "yes" } else { "no" }
+                self.get_level().get_ticks(),
+                self.notice,
             ),
-        }
+            Some(State::Lose) => "You have lost!\nR - reload".to_string(),
+            None => {
+                let mut header = vec![];
+                if self.levels.len() > 1 {
+                    header.push(format!(
+                        "Level {}/{}",
+                        self.level_idx + 1,
+                        self.levels.len()
+                    ));
+                }
+                header.extend(self.get_level().get_title().map(str::to_string));
+
+                let mut status = header.join(": ");
+                if !status.is_empty() {
+                    status.push('\n');
+                }
+                status += &format!(
+                    "Gems: {}/{}\nScore: {}\nDelay: {}ms\nPaused: {}",
+                    self.get_level().get_gems(),
+                    self.get_level().get_quota(),
+                    self.get_level().get_score(),
+                    self.delay.as_millis(),
+                    if self.pause { "yes" } else { "no" }
+                );
+
+                if let Some(time) = self.get_level().get_time_left() {
+                    status += &format!("\nTime: {time}");
+                }
+
+                let players = self.get_level().get_players();
+                if players.len() > 1 {
+                    let alive = players.iter().flatten().count();
+                    status += &format!("\nPlayers: {alive}/{}", players.len());
+                }
+
+                status + &best + &self.notice
+            }
+        };
+        status + &debug
     }
 }
 
@@ -56,57 +136,277 @@ impl Game {
     fn get_level_mut(&mut self) -> &mut Level {
         &mut self.levels[self.level_idx]
     }
-    fn get_level_path(&self) -> &str {
-        &self.level_paths[self.level_idx]
+    fn get_save_path(&self) -> String {
+        format!("{}.save", self.level_sources[0].0)
     }
 
-    pub fn new(args: &Arguments) -> io::Result<Self> {
+    pub fn new(args: &Arguments) -> Result<Self, Box<dyn Error>> {
+        // The game goes on without the high scores, they aren't saved over the broken file
+        let (scores, notice) = match HighScores::load(args.scores_path.as_deref()) {
+            Ok(scores) => (scores, String::new()),
+            Err(e) => (
+                HighScores::default(),
+                format!("\nCan't load the high scores: {e}"),
+            ),
+        };
+
         let mut game = Self {
             pause: args.pause,
             delay: args.delay,
+            stats_path: args.stats_path.clone(),
+            scores,
+            notice,
             level_paths: args.level_paths.clone(),
+            watcher: Watcher::new(&args.level_paths),
+            debug: args.debug.then(Overlay::default),
             ..Default::default()
         };
+        (game.levels, game.level_sources) = Self::read_levels(&args.level_paths)?;
 
-        for path in &args.level_paths {
-            game.levels.push(Level::new(&fs::read_to_string(path)?));
+        if args.resume {
+            game.resume()?;
         }
 
         Ok(game)
     }
 
+    fn read_levels(paths: &[String]) -> Result<(Vec<Level>, Vec<Source>), Box<dyn Error>> {
+        let mut levels = vec![];
+        let mut sources = vec![];
+
+        for path in paths {
+            for (idx, level) in levels::read(path)?.iter().enumerate() {
+                levels.push(Level::new(level).map_err(|e| format!("{path}: {e}"))?);
+                sources.push((path.clone(), idx));
+            }
+        }
+
+        if levels.is_empty() {
+            return Err("No levels to play!".into());
+        }
+        Ok((levels, sources))
+    }
+
+    // The levels of the other files keep their state, true when the current level is replaced
+    fn reload_changed(&mut self, changed: &[String]) -> Result<bool, Box<dyn Error>> {
+        let (mut levels, mut sources) = (vec![], vec![]);
+        let mut old = self.level_sources.iter().enumerate().peekable();
+        let mut current = (self.level_idx, false);
+
+        for path in &self.level_paths {
+            // The levels read from this path before, a path given twice starts over at 0
+            let mut run = vec![];
+            loop {
+                let first = run.is_empty();
+                let Some(entry) = old.next_if(|&(_, (p, idx))| p == path && (first || *idx != 0))
+                else {
+                    break;
+                };
+                run.push(entry);
+            }
+
+            if changed.contains(path) {
+                let (new_levels, new_sources) = Self::read_levels(std::slice::from_ref(path))?;
+                if let Some(&(_, (_, idx))) = run.iter().find(|(i, _)| *i == self.level_idx) {
+                    current = (levels.len() + (*idx).min(new_levels.len() - 1), true);
+                }
+                levels.extend(new_levels);
+                sources.extend(new_sources);
+            } else {
+                for (i, source) in run {
+                    if i == self.level_idx {
+                        current = (levels.len(), false);
+                    }
+                    levels.push(self.levels[i].clone());
+                    sources.push(source.clone());
+                }
+            }
+        }
+
+        if current.1 && self.get_level().get_state().is_none() {
+            self.finish_run("restart");
+        }
+        (self.levels, self.level_sources) = (levels, sources);
+        self.level_idx = current.0;
+        Ok(current.1)
+    }
+
+    fn save(&self) -> io::Result<()> {
+        let contents = format!("level {}\n{}", self.level_idx, self.get_level().save());
+        fs::write(self.get_save_path(), contents)
+    }
+
+    fn resume(&mut self) -> Result<(), Box<dyn Error>> {
+        let contents = fs::read_to_string(self.get_save_path())?;
+        let (level_idx, level) = contents
+            .split_once('\n')
+            .and_then(|(line, level)| {
+                Some((line.strip_prefix("level ")?.parse::<usize>().ok()?, level))
+            })
+            .ok_or("Missing the level number in the save!")?;
+
+        if level_idx >= self.levels.len() {
+            return Err(format!("The save is for level {level_idx}, which wasn't given!").into());
+        }
+
+        // Saves of the levels edited since then or from other packs don't fit
+        let level = Level::load(level)?;
+        if level.get_hash() != self.levels[level_idx].get_hash() {
+            return Err(
+                format!("The save doesn't match level {level_idx}, it has changed!").into(),
+            );
+        }
+
+        self.levels[level_idx] = level;
+        self.level_idx = level_idx;
+        Ok(())
+    }
+
+    // Write errors are shown in the status, the game goes on without the stats
+    fn finish_run(&mut self, outcome: &str) {
+        let level = self.get_level();
+        // Nothing to report before the first tick
+        let Some(path) = self.stats_path.as_ref().filter(|_| *level.get_ticks() > 0) else {
+            return;
+        };
+
+        let run = level.get_stats().to_json(
+            self.level_idx,
+            level.get_title(),
+            outcome,
+            *level.get_ticks(),
+            self.play_time,
+        );
+        self.runs.push(run);
+        self.play_time = Duration::ZERO;
+
+        if let Err(e) = fs::write(path, format!("[\n  {}\n]\n", self.runs.join(",\n  "))) {
+            self.notice += &format!("\nCan't save the stats: {e}");
+        }
+    }
+
+    fn reload(&mut self) -> Result<(), Box<dyn Error>> {
+        let (path, idx) = &self.level_sources[self.level_idx];
+        let level = levels::read(path)?
+            .get(*idx)
+            .map(|level| Level::new(level))
+            .ok_or_else(|| format!("{path}: level {idx} is missing!"))?
+            .map_err(|e| format!("{path}: {e}"))?;
+
+        self.levels[self.level_idx] = level;
+        Ok(())
+    }
+
+    fn change_delay(&mut self, faster: bool) {
+        if faster && self.delay.as_millis() >= 100 {
+            self.delay -= Duration::from_millis(50);
+        } else if !faster && self.delay.as_millis() <= 950 {
+            self.delay += Duration::from_millis(50);
+        }
+    }
+
     pub fn run(&mut self, interaction: &mut Mode) -> Result<(), Box<dyn Error>> {
-        let mut direction = None;
-        let mut paused_on_start = true;
-        let mut timer = Instant::now();
+        match self.main_menu(interaction)? {
+            Action::Quit => return Ok(()),
+            Action::Level(idx) => {
+                self.level_idx = idx;
+                self.reload()?;
+            }
+            Action::Play | Action::Restart | Action::Save => (),
+        }
 
-        interaction.draw(self)?;
+        // Moves pressed between the ticks, one per tick is used
+        let mut directions: [VecDeque<Move>; 2] = Default::default();
+        let mut paused_on_start = true;
+        let mut next_tick = interaction.now();
+        let mut redraw = true;
 
         loop {
-            thread::sleep(Duration::from_millis(10));
+            if redraw {
+                interaction.draw(self)?;
+            }
 
-            let input = interaction.get_input();
-            match input {
-                Input::Quit | Input::Q => return Ok(()),
-                Input::Comma => {
-                    if self.delay.as_millis() >= 100 {
-                        self.delay -= Duration::from_millis(50);
+            // Nothing happens until an input, no need to wake up for the ticks
+            let has_directions = directions.iter().any(|d| !d.is_empty());
+            let idle = self.get_level().get_state().is_some()
+                || paused_on_start
+                || (self.pause && !has_directions);
+            let timeout = if idle {
+                self.watcher.is_watching().then_some(POLL_INTERVAL)
+            } else {
+                Some(cmp::min(
+                    next_tick.saturating_duration_since(interaction.now()),
+                    POLL_INTERVAL,
+                ))
+            };
+
+            let waited_at = interaction.now();
+            let input = interaction.wait_input(timeout);
+            redraw = input != Input::Unknown;
+            // Measured while the level runs, the menus and the pauses don't count
+            if !idle {
+                self.play_time += interaction.now().saturating_duration_since(waited_at);
+            }
+
+            // Levels edited on disk are replaced, broken files are reported.
+            // The input is handled after the reload, so it applies to the new levels
+            let changed = self.watcher.changed();
+            if !changed.is_empty() {
+                self.notice = "\nReloaded the changed levels".to_string();
+                match self.reload_changed(&changed) {
+                    // The current level starts over only when its own file has changed
+                    Ok(true) => {
+                        self.play_time = Duration::ZERO;
+                        directions = Default::default();
+                        paused_on_start = true;
                     }
+                    Ok(false) => (),
+                    Err(err) => self.notice = format!("\n{err}"),
                 }
-                Input::Period => {
-                    if self.delay.as_millis() <= 950 {
-                        self.delay += Duration::from_millis(50);
+                redraw = true;
+            }
+
+            let action = match input {
+                Input::Quit => Action::Quit,
+                Input::Q => self.confirm_quit(interaction)?,
+                Input::Esc => self.pause_menu(interaction)?,
+                Input::R => Action::Restart,
+                Input::E => Action::Save,
+                _ => Action::Play,
+            };
+
+            match action {
+                Action::Quit => {
+                    if self.get_level().get_state().is_none() {
+                        self.finish_run("quit");
                     }
+                    return Ok(());
+                }
+                Action::Save => {
+                    self.save()?;
+                    self.notice = format!("\nSaved to {}", self.get_save_path());
+                    continue;
                 }
-                Input::Esc | Input::Space => self.pause = !self.pause,
-                Input::R => {
-                    self.levels[self.level_idx] =
-                        Level::new(&fs::read_to_string(self.get_level_path())?);
-                    direction = None;
+                Action::Restart | Action::Level(_) => {
+                    if self.get_level().get_state().is_none() {
+                        self.finish_run("restart");
+                    }
+                    if let Action::Level(idx) = action {
+                        self.level_idx = idx;
+                    }
+                    self.play_time = Duration::ZERO;
+                    self.reload()?;
+                    directions = Default::default();
                     paused_on_start = true;
-                    interaction.draw(self)?;
                     continue;
                 }
+                Action::Play => (),
+            }
+
+            match input {
+                Input::Comma => self.change_delay(true),
+                Input::Period => self.change_delay(false),
+                Input::Space => self.pause = !self.pause,
 
                 Input::Up
                 | Input::Down
@@ -115,37 +415,141 @@ impl Game {
                 | Input::W
                 | Input::A
                 | Input::S
-                | Input::D => direction = Direction::try_from(input.clone()).ok(),
-
-                Input::Unknown => (),
-            }
+                | Input::D
+                | Input::Snap(_) => {
+                    // A single player is controlled by both key sets
+                    let player = if self.get_level().get_players().len() > 1 {
+                        input.player()
+                    } else {
+                        0
+                    };
+                    if directions[player].len() < MAX_BUFFERED_DIRECTIONS {
+                        directions[player].extend(Move::try_from(input.clone()));
+                    }
+                }
 
-            if let Some(state) = self.get_level().get_state() {
-                if *state == State::Win && self.level_idx + 1 < self.levels.len() {
-                    self.level_idx += 1;
-                    interaction.draw(self)?;
+                // The debug cursor
+                Input::I | Input::J | Input::K | Input::L => {
+                    let direction = match input {
+                        Input::I => Direction::Up,
+                        Input::J => Direction::Left,
+                        Input::K => Direction::Down,
+                        _ => Direction::Right,
+                    };
+                    if let Some(overlay) = &mut self.debug {
+                        overlay.move_cursor(&self.levels[self.level_idx], direction);
+                        self.redraw_all = true;
+                    }
                 }
-                continue;
+
+                Input::Quit
+                | Input::Q
+                | Input::Esc
+                | Input::R
+                | Input::E
+                | Input::N
+                | Input::Enter
+                | Input::PageUp
+                | Input::PageDown
+                | Input::Unknown => (),
             }
 
-            if timer.elapsed() < self.delay {
-                if input != Input::Unknown {
-                    interaction.draw(self)?;
-                }
+            if self.get_level().get_state().is_some() {
                 continue;
             }
 
-            timer = Instant::now();
-
-            if paused_on_start && direction.is_some() {
+            // A single tick out of the schedule
+            let step = self.debug.is_some() && input == Input::N;
+            let has_directions = directions.iter().any(|d| !d.is_empty());
+            if paused_on_start && (has_directions || step) {
                 paused_on_start = false;
+                next_tick = interaction.now();
             }
-            if (self.pause && direction.is_none()) || paused_on_start {
-                continue;
+            if !step {
+                if (self.pause && !has_directions) || paused_on_start {
+                    continue;
+                }
+
+                let now = interaction.now();
+                if now < next_tick {
+                    continue;
+                }
+                // Fixed time step, the missed ticks are skipped
+                next_tick = cmp::max(next_tick + self.delay, now);
+            }
+
+            self.notice.clear();
+            let tick_directions = directions.each_mut().map(VecDeque::pop_front);
+            self.get_level_mut().tick(&tick_directions);
+            self.moves = self
+                .get_level()
+                .get_requests()
+                .iter()
+                .filter_map(|request| match request {
+                    Request::MoveObj { from, to } => Some((*from, *to)),
+                    _ => None,
+                })
+                // Wrapping around the edges isn't animated
+                .filter(|&(from, to)| !Level::crosses_edge(from, to))
+                .collect();
+            if let Some(overlay) = &mut self.debug {
+                overlay.record(&self.levels[self.level_idx]);
             }
+            redraw = true;
 
-            self.get_level_mut().tick(direction.take());
-            interaction.draw(self)?;
+            match self.get_level().get_state() {
+                Some(State::Win) => {
+                    let level = self.get_level();
+                    let entry = Entry::new(*level.get_score(), *level.get_ticks());
+                    if let Err(e) = self.scores.record(level.get_hash(), entry) {
+                        self.notice = format!("\nCan't save the high score: {e}");
+                    }
+                    self.finish_run("win");
+
+                    if self.level_idx + 1 < self.levels.len() {
+                        self.level_idx += 1;
+                        self.moves.clear();
+                        directions = Default::default();
+                    }
+                }
+                Some(State::Lose) => self.finish_run("lose"),
+                None => (),
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn reload_only_the_changed_files() {
+        let dir = env::temp_dir().join(format!("boulder_dash_reload_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = |name: &str| dir.join(name).to_string_lossy().into_owned();
+        fs::write(path("a"), "#p +#\n").unwrap();
+        fs::write(path("b"), "@level B1\n#p+#\n@level B2\n#+p#\n").unwrap();
+
+        let args = Arguments {
+            level_paths: vec![path("a"), path("b")],
+            ..Default::default()
+        };
+        let mut game = Game::new(&args).unwrap();
+        game.get_level_mut().step(Some(Direction::Right.into()));
+
+        // Another file, the current run goes on
+        fs::write(path("b"), "@level B1\n#p++#\n").unwrap();
+        assert!(!game.reload_changed(&[path("b")]).unwrap());
+        assert_eq!(game.levels.len(), 2);
+        assert_eq!((game.level_idx, *game.get_level().get_ticks()), (0, 1));
+        assert_eq!(game.levels[1].get_objects().get_width(), 5);
+
+        // The current file, the level starts over
+        fs::write(path("a"), "#p  +#\n").unwrap();
+        assert!(game.reload_changed(&[path("a")]).unwrap());
+        assert_eq!((game.level_idx, *game.get_level().get_ticks()), (0, 0));
+        assert_eq!(game.get_level().get_objects().get_width(), 6);
+    }
+}
@@ -0,0 +1,81 @@
+use super::level::Level;
+use crate::{
+    direction::Direction,
+    objects::{Labels, Object, Properties},
+    Point,
+};
+
+const MAX_LISTED: usize = 8;
+
+// What the last tick did and the object under the cursor, shown under the status
+#[derive(Default)]
+pub struct Overlay {
+    pub cursor: Point,
+    damaged: Vec<Point>, // by the last tick
+}
+
+impl Overlay {
+    pub fn move_cursor(&mut self, level: &Level, direction: Direction) {
+        if let Some(point) = level.get_objects().neighbour(self.cursor, direction) {
+            self.cursor = point;
+        }
+    }
+
+    // Has to be called right after the tick, drawing takes the damaged cells
+    pub fn record(&mut self, level: &Level) {
+        self.damaged = level.peek_damaged().iter().copied().collect();
+        self.damaged.sort_unstable_by_key(|&(x, y)| (y, x));
+    }
+
+    pub fn get_status(&self, level: &Level) -> String {
+        let requests: Vec<String> = level
+            .get_requests()
+            .iter()
+            .map(|request| format!("{request:?}"))
+            .collect();
+        let damaged: Vec<String> = self
+            .damaged
+            .iter()
+            .map(|(x, y)| format!("({x}, {y})"))
+            .collect();
+        let (x, y) = self.cursor;
+
+        format!(
+            "\nTick: {}\nRequests: {}\nDamaged: {}\nAt ({x}, {y}): {}",
+            level.get_ticks(),
+            list(&requests, "\n    "),
+            list(&damaged, " "),
+            describe(level.get_object(self.cursor)),
+        )
+    }
+}
+
+// Long lists are cut to keep the status readable
+fn list(items: &[String], separator: &str) -> String {
+    let mut listed = items.iter().take(MAX_LISTED).cloned().collect::<Vec<_>>();
+    if items.len() > MAX_LISTED {
+        listed.push(format!("and {} more", items.len() - MAX_LISTED));
+    }
+    if listed.is_empty() {
+        return "none".to_string();
+    }
+    format!("{}{separator}{}", items.len(), listed.join(separator))
+}
+
+fn describe(object: &Object) -> String {
+    let properties = [
+        ("placeholder", object.placeholder()),
+        ("can_be_moved", object.can_be_moved()),
+        ("player", object.player()),
+        ("can_be_broken", object.can_be_broken()),
+        ("rounded", object.rounded()),
+        ("indestructible", object.indestructible()),
+        ("enemy", object.enemy()),
+        ("exit", object.exit()),
+    ];
+    let mut description = format!("{} '{}'", object.name(), object.char());
+    for (name, _) in properties.iter().filter(|(_, set)| *set) {
+        description += &format!(" {name}");
+    }
+    description
+}
@@ -1,116 +1,491 @@
+use super::stats::Stats;
 use crate::{
-    direction::Direction,
-    objects::{Behaviour, Object, Properties},
-    Point,
+    direction::{Direction, Move},
+    grid::Grid,
+    levels::Metadata,
+    objects::{Behaviour, Labels, Object, Properties},
+    scores, Point,
 };
-use std::collections::HashSet;
+use std::collections::{HashSet, VecDeque};
 
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum State {
     Win,
     Lose,
 }
 
+#[derive(Debug, Clone)]
 pub enum Request {
-    AddScore,
+    AddScore(usize), // points
+    AddGem,
     AddMaxScore,
     UpdateState(State),
-    MoveObj { from: Point, to: Point }, // (from, to)
+    MoveObj { from: Point, to: Point },  // (from, to)
+    Spawn { at: Point, object: Object }, // only into the empty space
+    Remove { at: Point },                // leaves the empty space
+    // Clears the 3x3 square around, scored when the enemies in it were killed by the players
+    Explode { at: Point, scored: bool },
 }
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct Level {
+    hash: u64,
+    metadata: Metadata,
+    ticks: usize,
     score: usize,
-    max_score: usize,
-    player: Point,
+    gems: usize,
+    max_score: usize,            // gems in the level
+    players: Vec<Option<Point>>, // None when dead
     state: Option<State>,
+    stats: Stats,
+    requests: Vec<Request>, // handled during the last tick
     damaged: HashSet<Point>,
-    matrix: Vec<Vec<Object>>,
+    acted: HashSet<Point>, // objects spawned or moved ahead this tick, done until the next one
+    falling: HashSet<Point>, // objects that have fallen or rolled during the last tick
+    matrix: Grid,
 }
 
 // Getters
 impl Level {
+    pub const fn get_hash(&self) -> u64 {
+        self.hash
+    }
+    pub const fn get_ticks(&self) -> &usize {
+        &self.ticks
+    }
     pub const fn get_score(&self) -> &usize {
         &self.score
     }
-    pub const fn get_max_score(&self) -> &usize {
-        &self.max_score
+    pub const fn get_gems(&self) -> usize {
+        self.gems
+    }
+    // Gems past the quota are worth the extra points
+    pub fn get_gem_points(&self) -> usize {
+        let points = self.metadata.gem_points.unwrap_or(1);
+        if self.gems >= self.get_quota() {
+            self.metadata.extra_points.unwrap_or(points)
+        } else {
+            points
+        }
+    }
+    pub fn get_title(&self) -> Option<&str> {
+        self.metadata.title.as_deref()
+    }
+    pub fn get_quota(&self) -> usize {
+        self.metadata.quota.unwrap_or(self.max_score)
+    }
+    pub fn get_time_left(&self) -> Option<usize> {
+        Some(self.metadata.time?.saturating_sub(self.ticks))
+    }
+    pub const fn get_stats(&self) -> &Stats {
+        &self.stats
     }
     pub const fn get_state(&self) -> &Option<State> {
         &self.state
     }
-    pub const fn get_player(&self) -> &Point {
-        &self.player
+    pub fn is_falling(&self, point: Point) -> bool {
+        self.falling.contains(&point)
+    }
+    pub const fn get_players(&self) -> &Vec<Option<Point>> {
+        &self.players
+    }
+    pub const fn get_requests(&self) -> &Vec<Request> {
+        &self.requests
     }
     pub fn get_damaged(&mut self) -> HashSet<Point> {
         std::mem::take(&mut self.damaged)
     }
-    pub fn get_object(&self, (x, y): Point) -> &Object {
-        &self.matrix[y][x]
+    pub const fn peek_damaged(&self) -> &HashSet<Point> {
+        &self.damaged
     }
-    pub const fn get_objects(&self) -> &Vec<Vec<Object>> {
+    pub fn get_gravity(&self) -> Direction {
+        self.metadata.gravity.unwrap_or(Direction::Down)
+    }
+    pub const fn get_wrap(&self) -> bool {
+        self.metadata.wrap
+    }
+    // Moves further than the next cell have gone around the edges
+    pub const fn crosses_edge(from: Point, to: Point) -> bool {
+        from.0.abs_diff(to.0) > 1 || from.1.abs_diff(to.1) > 1
+    }
+    // None when the neighbour is outside of the level
+    pub fn neighbour(&self, point: Point, direction: Direction) -> Option<Point> {
+        if self.metadata.wrap {
+            self.matrix.wrapping_neighbour(point, direction)
+        } else {
+            self.matrix.neighbour(point, direction)
+        }
+    }
+    // Out of bounds objects are walls
+    pub fn get_object(&self, point: Point) -> &Object {
+        self.matrix.get_or_border(point)
+    }
+    pub const fn get_objects(&self) -> &Grid {
         &self.matrix
     }
 }
 
 impl Level {
-    pub fn new(string: &str) -> Self {
-        let mut level = Self::default();
-        for (y, line) in string.trim().lines().enumerate() {
+    pub fn new(string: &str) -> Result<Self, String> {
+        let (metadata, matrix) = Metadata::parse(string)?;
+        let mut level = Self {
+            hash: scores::hash(string.trim()),
+            metadata,
+            ..Default::default()
+        };
+        let mut rows = vec![];
+        for (y, line) in matrix.trim().lines().enumerate() {
             let mut row = vec![];
 
             for (x, chr) in line.trim().chars().enumerate() {
                 let obj = Object::new(chr);
                 level.handle_requests(obj.init());
                 if obj.player() {
-                    level.player = (x, y);
+                    level.players.push(Some((x, y)));
                 }
-
-                level.damaged.insert((x, y));
                 row.push(obj);
             }
-            level.matrix.push(row);
+            rows.push(row);
         }
 
-        level
+        level.matrix = Grid::new(rows);
+        level.damaged.extend(level.matrix.points());
+        // Only the ticks are recorded
+        level.requests.clear();
+        Ok(level)
+    }
+
+    // Gravity side first, so that the stacked rocks fall together
+    fn tick_order(&self, (x, y): Point) -> (usize, usize) {
+        let (width, height) = (self.matrix.get_width(), self.matrix.get_height());
+        match self.get_gravity() {
+            Direction::Down => (height.saturating_sub(y + 1), x),
+            Direction::Up => (y, x),
+            Direction::Left => (x, y),
+            Direction::Right => (width.saturating_sub(x + 1), y),
+        }
     }
 
+    // The handled requests are always kept until the next tick, the moves are animated from them
     fn handle_requests(&mut self, requests: Vec<Request>) {
-        for request in requests {
-            match request {
+        let mut requests = VecDeque::from(requests);
+        while let Some(request) = requests.pop_front() {
+            match &request {
                 Request::UpdateState(state) => {
                     if self.state.is_none() {
-                        self.state = Some(state);
+                        // The ticks left are worth points on a win
+                        if *state == State::Win {
+                            let bonus = self.metadata.time_bonus.unwrap_or(0);
+                            self.score += self.get_time_left().unwrap_or(0) * bonus;
+                        }
+                        self.state = Some(state.clone());
+                    }
+                }
+                Request::AddScore(points) => self.score += points,
+                Request::AddGem => {
+                    self.gems += 1;
+                    self.stats.gems += 1;
+
+                    // Levels with exits go on until a player steps into one
+                    let exits = self.matrix.points().any(|p| self.get_object(p).exit());
+                    if self.gems >= self.get_quota() && !exits {
+                        requests.push_back(Request::UpdateState(State::Win));
                     }
                 }
-                Request::AddScore => self.score += 1,
                 Request::AddMaxScore => self.max_score += 1,
-                Request::MoveObj { from, to } => {
-                    if self.get_object(from).player() {
-                        self.player = to;
+                &Request::MoveObj { from, to } => {
+                    // Moves to the neighbours, the rocks fall and roll along the gravity
+                    let direction = [
+                        Direction::Up,
+                        Direction::Down,
+                        Direction::Left,
+                        Direction::Right,
+                    ]
+                    .into_iter()
+                    .find(|&direction| self.neighbour(from, direction) == Some(to));
+                    let across = self.get_gravity().across();
+                    if self.get_object(from).can_be_moved()
+                        && direction.is_some_and(|direction| across.contains(&direction))
+                    {
+                        self.stats.rocks_pushed += 1;
+                    }
+
+                    let crushed = self.players.contains(&Some(to));
+                    for (idx, player) in self.players.iter_mut().enumerate() {
+                        if *player == Some(to) {
+                            *player = None;
+                            let cause =
+                                format!("crushed by {}", self.matrix.get_or_border(from).name());
+                            self.stats.deaths.push(cause);
+                        } else if *player == Some(from) {
+                            *player = Some(to);
+                            if let Some(direction) = direction {
+                                self.stats.add_move(idx, direction);
+                            }
+                        }
                     }
 
-                    self.matrix[to.1][to.0] = std::mem::take(&mut self.matrix[from.1][from.0]);
+                    let object = self.matrix.take(from);
+                    self.matrix.set(to, object);
+                    // Objects moved ahead of the ticking order aren't ticked again
+                    if self.tick_order(to) > self.tick_order(from) {
+                        self.acted.insert(to);
+                    }
                     self.damaged.extend([from, to]);
+
+                    // Crushed players blow up
+                    if crushed {
+                        requests.push_front(Request::Explode {
+                            at: to,
+                            scored: true,
+                        });
+                    }
+                    if self.state.is_none() && self.players.iter().all(Option::is_none) {
+                        self.state = Some(State::Lose);
+                    }
+                }
+                &Request::Explode { at: (x, y), scored } => {
+                    let square = (y.saturating_sub(1)..=y + 1)
+                        .flat_map(|y| (x.saturating_sub(1)..=x + 1).map(move |x| (x, y)));
+                    for point in square {
+                        if self
+                            .matrix
+                            .get(point)
+                            .is_none_or(Properties::indestructible)
+                        {
+                            continue;
+                        }
+
+                        if scored && self.get_object(point).enemy() {
+                            self.score += self.metadata.enemy_points.unwrap_or(0);
+                        }
+                        for player in &mut self.players {
+                            if *player == Some(point) {
+                                *player = None;
+                                self.stats.deaths.push("caught in an explosion".to_string());
+                            }
+                        }
+                        self.matrix.set(point, Object::default());
+                        self.acted.insert(point);
+                        self.damaged.insert(point);
+                    }
+
+                    if self.state.is_none() && self.players.iter().all(Option::is_none) {
+                        self.state = Some(State::Lose);
+                    }
+                }
+                &Request::Remove { at } => {
+                    self.matrix.set(at, Object::default());
+                    self.damaged.insert(at);
+                }
+                &Request::Spawn { at, ref object } => {
+                    if self.matrix.get(at).is_some_and(Properties::placeholder) {
+                        self.matrix.set(at, object.clone());
+                        self.acted.insert(at);
+                        self.damaged.insert(at);
+                    }
                 }
             }
+            self.requests.push(request);
         }
     }
 
-    pub fn tick(&mut self, direction: Option<Direction>) {
-        // Player
-        let requests = self
-            .get_object(self.player)
-            .tick(self, self.player, direction);
-        self.handle_requests(requests);
+    // Moves of the players in order of their appearance in the level
+    pub fn tick(&mut self, moves: &[Option<Move>]) {
+        self.ticks += 1;
+
+        // Moves along the gravity, the pushes go across it
+        let across = self.get_gravity().across();
+        self.falling = std::mem::take(&mut self.requests)
+            .into_iter()
+            .filter_map(|request| match request {
+                Request::MoveObj { from, to } => Some((from, to)),
+                _ => None,
+            })
+            .filter(|&(from, to)| {
+                !across
+                    .iter()
+                    .any(|&side| self.neighbour(from, side) == Some(to))
+            })
+            .map(|(_, to)| to)
+            .collect();
 
-        // Rocks
-        for y in (0..self.matrix.len()).rev() {
-            for x in 0..self.matrix[y].len() {
-                if self.matrix[y][x].can_be_moved() {
-                    self.handle_requests(self.matrix[y][x].tick(self, (x, y), None));
+        // Players
+        for (i, player) in self.players.clone().into_iter().enumerate() {
+            // Could have been crushed by the previous player
+            let Some(player) = player.filter(|&p| self.get_object(p).player()) else {
+                continue;
+            };
+            let movement = moves.get(i).copied().flatten();
+
+            let requests = self.get_object(player).tick(self, player, movement);
+            self.handle_requests(requests);
+        }
+
+        // Everything else
+        let mut points: Vec<Point> = self.matrix.points().collect();
+        points.sort_by_key(|&point| self.tick_order(point));
+
+        self.acted.clear();
+        for point in points {
+            if !self.get_object(point).player() && !self.acted.contains(&point) {
+                self.handle_requests(self.get_object(point).tick(self, point, None));
+            }
+        }
+
+        if self.get_time_left() == Some(0) && self.state.is_none() {
+            self.stats.deaths.push("time ran out".to_string());
+            self.handle_requests(vec![Request::UpdateState(State::Lose)]);
+        }
+    }
+
+    // A tick of the first player, does nothing once the level is over
+    pub fn step(&mut self, movement: Option<Move>) -> &Option<State> {
+        if self.state.is_none() {
+            self.tick(&[movement]);
+        }
+        &self.state
+    }
+}
+
+// Snapshots
+impl Level {
+    pub fn save(&self) -> String {
+        let state = match self.state {
+            Some(State::Win) => "win",
+            Some(State::Lose) => "lose",
+            None => "none",
+        };
+        let players: Vec<String> = self
+            .players
+            .iter()
+            .map(|player| player.map_or_else(|| "-".to_string(), |(x, y)| format!("{x},{y}")))
+            .collect();
+
+        let stats = &self.stats;
+        let directions: Vec<String> = stats
+            .last_directions
+            .iter()
+            .map(|direction| direction.map_or_else(|| "-".to_string(), |d| d.to_string()))
+            .collect();
+
+        // Nothing else is kept between the ticks, the slime walls only count them
+        let mut contents = self.metadata.to_string();
+        contents += &format!(
+            "hash {:016x}\nticks {}\nscore {}\ngems {}\nmax_score {}\nstate {state}\nplayers {}\n\
+            stats {} {} {} {}\ndirections {}\n",
+            self.hash,
+            self.ticks,
+            self.score,
+            self.gems,
+            self.max_score,
+            players.join(" "),
+            stats.moves,
+            stats.direction_changes,
+            stats.rocks_pushed,
+            stats.gems,
+            directions.join(" "),
+        );
+        for cause in &stats.deaths {
+            contents += &format!("death {cause}\n");
+        }
+        contents.push('\n');
+        for row in self.matrix.rows() {
+            contents.extend(row.iter().map(Labels::char));
+            contents.push('\n');
+        }
+
+        contents
+    }
+
+    pub fn load(string: &str) -> Result<Self, String> {
+        let (metadata, string) = Metadata::parse(string)?;
+        let (header, matrix) = string
+            .split_once("\n\n")
+            .ok_or("Missing the level in the save!")?;
+        let mut level = Self {
+            metadata,
+            ..Default::default()
+        };
+        // Older saves scored a point per gem
+        let mut gems = None;
+
+        for line in header.lines() {
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            let number = || -> Result<usize, String> {
+                value
+                    .parse()
+                    .map_err(|_| format!("Can't parse `{value}` as `{key}`!"))
+            };
+
+            match key {
+                "hash" => {
+                    level.hash = u64::from_str_radix(value, 16)
+                        .map_err(|_| format!("Can't parse `{value}` as a hash!"))?;
+                }
+                "ticks" => level.ticks = number()?,
+                "score" => level.score = number()?,
+                "gems" => gems = Some(number()?),
+                "max_score" => level.max_score = number()?,
+                "state" => {
+                    level.state = match value {
+                        "win" => Some(State::Win),
+                        "lose" => Some(State::Lose),
+                        "none" => None,
+                        _ => return Err(format!("Can't parse `{value}` as a state!")),
+                    }
+                }
+                "players" => {
+                    for player in value.split_whitespace() {
+                        let point = match player.split_once(',') {
+                            Some((x, y)) => Some((
+                                x.parse().map_err(|_| format!("Bad player `{player}`!"))?,
+                                y.parse().map_err(|_| format!("Bad player `{player}`!"))?,
+                            )),
+                            None => None,
+                        };
+                        level.players.push(point);
+                    }
+                }
+                "stats" => {
+                    let numbers: Vec<usize> = value
+                        .split_whitespace()
+                        .map(str::parse)
+                        .collect::<Result<_, _>>()
+                        .map_err(|_| format!("Can't parse `{value}` as the statistics!"))?;
+                    let [moves, direction_changes, rocks_pushed, gems] = numbers[..] else {
+                        return Err(format!("Can't parse `{value}` as the statistics!"));
+                    };
+                    let stats = &mut level.stats;
+                    (stats.moves, stats.direction_changes) = (moves, direction_changes);
+                    (stats.rocks_pushed, stats.gems) = (rocks_pushed, gems);
                 }
+                "directions" => {
+                    for direction in value.split_whitespace() {
+                        let direction = match direction {
+                            "-" => None,
+                            direction => Some(direction.parse()?),
+                        };
+                        level.stats.last_directions.push(direction);
+                    }
+                }
+                "death" => level.stats.deaths.push(value.to_string()),
+                _ => return Err(format!("Unrecognized save entry `{key}`!")),
             }
         }
+        level.gems = gems.unwrap_or(level.score);
+
+        // Rows are stored as is, without trimming
+        let rows = matrix.lines();
+        level.matrix = Grid::new(rows.map(|l| l.chars().map(Object::new).collect()).collect());
+        level.damaged.extend(level.matrix.points());
+
+        for &(x, y) in level.players.iter().flatten() {
+            if !level.matrix.get((x, y)).is_some_and(Properties::player) {
+                return Err(format!("No player at ({x}, {y}) in the save!"));
+            }
+        }
+
+        Ok(level)
     }
 }
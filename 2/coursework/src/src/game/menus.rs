@@ -0,0 +1,142 @@
+use super::Game;
+use crate::{
+    interaction::Mode,
+    menu::{Choice, Menu},
+};
+use std::error::Error;
+
+// What the player picked in a menu
+#[derive(Clone, Copy)]
+pub enum Action {
+    Play,
+    Restart,
+    Save,
+    Level(usize),
+    Quit,
+}
+
+// Items shared by the main and the pause menus, after their own actions
+enum Shared {
+    LevelSelect,
+    Settings,
+    Quit,
+}
+
+impl Game {
+    pub(super) fn main_menu(&mut self, interaction: &mut Mode) -> Result<Action, Box<dyn Error>> {
+        let title = match self.levels.len() {
+            1 => self
+                .get_level()
+                .get_title()
+                .unwrap_or("Boulder Dash")
+                .to_string(),
+            count => format!("Boulder Dash: {count} levels"),
+        };
+        self.menu(interaction, &title, &[("Play", Action::Play)], None)
+    }
+
+    pub(super) fn pause_menu(&mut self, interaction: &mut Mode) -> Result<Action, Box<dyn Error>> {
+        let actions = [
+            ("Resume", Action::Play),
+            ("Restart", Action::Restart),
+            ("Save", Action::Save),
+        ];
+        self.menu(interaction, "Paused", &actions, Some(Action::Play))
+    }
+
+    pub(super) fn confirm_quit(
+        &mut self,
+        interaction: &mut Mode,
+    ) -> Result<Action, Box<dyn Error>> {
+        self.redraw_all = true;
+        let mut menu = Menu::new("Quit the game?", vec!["No".into(), "Yes".into()]);
+
+        Ok(match menu.run(interaction)? {
+            Choice::Select(1) | Choice::Quit => Action::Quit,
+            Choice::Select(_) | Choice::Back => Action::Play,
+        })
+    }
+
+    // Going back picks the `back` action, or stays in the menu without it
+    fn menu(
+        &mut self,
+        interaction: &mut Mode,
+        title: &str,
+        actions: &[(&str, Action)],
+        back: Option<Action>,
+    ) -> Result<Action, Box<dyn Error>> {
+        self.redraw_all = true;
+
+        let mut shared = vec![];
+        if self.levels.len() > 1 {
+            shared.push(("Level select", Shared::LevelSelect));
+        }
+        shared.extend([("Settings", Shared::Settings), ("Quit", Shared::Quit)]);
+
+        let items = actions
+            .iter()
+            .map(|(item, _)| item)
+            .chain(shared.iter().map(|(item, _)| item));
+        let mut menu = Menu::new(title, items.map(ToString::to_string).collect());
+
+        loop {
+            let idx = match menu.run(interaction)? {
+                Choice::Select(idx) => idx,
+                Choice::Back => match back {
+                    Some(action) => return Ok(action),
+                    None => continue,
+                },
+                Choice::Quit => return Ok(Action::Quit),
+            };
+
+            if let Some(&(_, action)) = actions.get(idx) {
+                return Ok(action);
+            }
+            let picked = match shared[idx - actions.len()].1 {
+                Shared::LevelSelect => self.level_select(interaction)?,
+                Shared::Settings => self.settings(interaction)?,
+                Shared::Quit => Some(Action::Quit),
+            };
+            if let Some(action) = picked {
+                return Ok(action);
+            }
+        }
+    }
+
+    fn level_select(&mut self, interaction: &mut Mode) -> Result<Option<Action>, Box<dyn Error>> {
+        let items = self.levels.iter().enumerate().map(|(idx, level)| {
+            let title = level.get_title().unwrap_or("Untitled");
+            format!("{}: {title}", idx + 1)
+        });
+        let mut menu = Menu::new("Level select", items.collect());
+        menu.select(self.level_idx);
+
+        Ok(match menu.run(interaction)? {
+            Choice::Select(idx) => Some(Action::Level(idx)),
+            Choice::Back => None,
+            Choice::Quit => Some(Action::Quit),
+        })
+    }
+
+    fn settings(&mut self, interaction: &mut Mode) -> Result<Option<Action>, Box<dyn Error>> {
+        let mut menu = Menu::default();
+
+        loop {
+            menu.set_title(&format!("Settings\nDelay: {}ms", self.delay.as_millis()));
+            menu.set_items(vec![
+                "Faster".into(),
+                "Slower".into(),
+                format!("Paused: {}", if self.pause { "yes" } else { "no" }),
+                "Back".into(),
+            ]);
+
+            match menu.run(interaction)? {
+                Choice::Select(0) => self.change_delay(true),
+                Choice::Select(1) => self.change_delay(false),
+                Choice::Select(2) => self.pause = !self.pause,
+                Choice::Select(_) | Choice::Back => return Ok(None),
+                Choice::Quit => return Ok(Some(Action::Quit)),
+            }
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use crate::direction::Direction;
+use std::time::Duration;
+
+pub fn json_string(string: &str) -> String {
+    let mut escaped = String::from('"');
+    for chr in string.chars() {
+        match chr {
+            '"' => escaped += "\\\"",
+            '\\' => escaped += "\\\\",
+            '\n' => escaped += "\\n",
+            chr if chr.is_control() => escaped += &format!("\\u{:04x}", u32::from(chr)),
+            chr => escaped.push(chr),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+// Counted by the level from the handled requests
+#[derive(Default, Clone)]
+pub struct Stats {
+    pub moves: usize,
+    pub direction_changes: usize,
+    pub rocks_pushed: usize,
+    pub gems: usize,
+    pub deaths: Vec<String>,                     // causes
+    pub last_directions: Vec<Option<Direction>>, // for every player
+}
+
+impl Stats {
+    pub fn add_move(&mut self, player: usize, direction: Direction) {
+        if self.last_directions.len() <= player {
+            self.last_directions.resize(player + 1, None);
+        }
+
+        let last = self.last_directions[player].replace(direction);
+        if last.is_some_and(|last| last != direction) {
+            self.direction_changes += 1;
+        }
+        self.moves += 1;
+    }
+
+    pub fn to_json(
+        &self,
+        level: usize,
+        title: Option<&str>,
+        outcome: &str,
+        ticks: usize,
+        time: Duration,
+    ) -> String {
+        let minutes = time.as_secs_f64() / 60.0;
+        let gems_per_minute = if minutes > 0.0 {
+            self.gems as f64 / minutes
+        } else {
+            0.0
+        };
+        let causes: Vec<String> = self.deaths.iter().map(|c| json_string(c)).collect();
+
+        format!(
+            "{{\"level\": {level}, \"title\": {}, \"outcome\": {}, \"ticks\": {ticks}, \
+            \"seconds\": {:.3}, \"moves\": {}, \"direction_changes\": {}, \"gems\": {}, \
+            \"gems_per_minute\": {gems_per_minute:.3}, \"rocks_pushed\": {}, \"deaths\": {}, \
+            \"causes_of_death\": [{}]}}",
+            title.map_or_else(|| "null".to_string(), json_string),
+            json_string(outcome),
+            time.as_secs_f64(),
+            self.moves,
+            self.direction_changes,
+            self.gems,
+            self.rocks_pushed,
+            self.deaths.len(),
+            causes.join(", "),
+        )
+    }
+}
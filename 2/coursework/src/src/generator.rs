@@ -0,0 +1,189 @@
+use crate::{
+    args::Arguments,
+    game::level::Level,
+    levels::Metadata,
+    objects::{Labels, Object, Properties},
+    random::Random,
+    Point,
+};
+use std::{
+    collections::{HashSet, VecDeque},
+    error::Error,
+    fs,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+// Rarer objects go last, they win over the previous ones
+const DEFAULT_FILL: [(char, u8); 3] = [(' ', 60), ('O', 45), ('+', 15)];
+
+// Like the original game: dirt everywhere, then every cell rolls the random fill
+fn fill(args: &Arguments, seed: u8) -> String {
+    let (width, height) = args.cave_size;
+    let mut matrix = vec![vec!['*'; width]; height];
+    let densities = if args.fill.is_empty() {
+        &DEFAULT_FILL[..]
+    } else {
+        &args.fill
+    };
+
+    let mut random = Random::new(seed);
+    for row in &mut matrix {
+        for cell in row.iter_mut() {
+            let value = random.next();
+            for &(object, probability) in densities {
+                if value < probability {
+                    *cell = object;
+                }
+            }
+        }
+    }
+
+    if args.walled {
+        for (y, row) in matrix.iter_mut().enumerate() {
+            for (x, cell) in row.iter_mut().enumerate() {
+                if x == 0 || y == 0 || x + 1 == width || y + 1 == height {
+                    *cell = '%';
+                }
+            }
+        }
+    }
+
+    // The player starts inside the border, not under a rock
+    let margin = usize::from(args.walled);
+    let inner = |size: usize| size.saturating_sub(2 * margin).max(1);
+    let x = margin + usize::from(random.next()) % inner(width);
+    let y = margin + usize::from(random.next()) % inner(height);
+    matrix[y][x] = 'p';
+    if y > margin {
+        matrix[y - 1][x] = '*';
+    }
+
+    let metadata = Metadata {
+        title: Some(format!("Generated cave {seed}")),
+        quota: Some(args.gems),
+        ..Default::default()
+    };
+    let mut contents = metadata.to_string();
+    for row in matrix {
+        contents.extend(row);
+        contents.push('\n');
+    }
+    contents
+}
+
+// Gems the player can dig to without moving any rocks
+fn reachable_gems(level: &Level) -> usize {
+    let grid = level.get_objects();
+    let passable = |point: Point| {
+        let obj = grid.get_or_border(point);
+        obj.placeholder() || obj.can_be_broken()
+    };
+
+    let mut visited: HashSet<Point> = level.get_players().iter().flatten().copied().collect();
+    let mut queue: VecDeque<Point> = visited.iter().copied().collect();
+    while let Some(point) = queue.pop_front() {
+        for (_, next) in grid.neighbours(point, false) {
+            if passable(next) && visited.insert(next) {
+                queue.push_back(next);
+            }
+        }
+    }
+
+    visited
+        .into_iter()
+        .filter(|&p| grid.get_or_border(p).collectible())
+        .count()
+}
+
+// Seeds are tried in order until the quota can be collected
+pub fn run(args: &Arguments) -> Result<(), Box<dyn Error>> {
+    let path = &args.level_paths[0];
+    if Path::new(path).exists() {
+        return Err(format!("{path} already exists!").into());
+    }
+    let objects = Object::get_all_displayable();
+    if let Some((object, _)) = args
+        .fill
+        .iter()
+        .find(|(c, _)| !objects.iter().any(|o| o.char() == *c))
+    {
+        return Err(format!("Unknown object `{object}` in the fill!").into());
+    }
+
+    let first_seed = args.seed.unwrap_or_else(|| {
+        let time = SystemTime::now().duration_since(UNIX_EPOCH);
+        time.map_or(0, |t| u8::try_from(t.as_millis() % 256).unwrap_or(0))
+    });
+
+    for seed in (0..=u8::MAX).map(|offset| first_seed.wrapping_add(offset)) {
+        let contents = fill(args, seed);
+        let level = Level::new(&contents)?;
+        if reachable_gems(&level) >= args.gems {
+            fs::write(path, contents)?;
+            println!("{path}: seed {seed}");
+            return Ok(());
+        }
+    }
+
+    Err(format!(
+        "No seed gives {} reachable gems, try a smaller quota!",
+        args.gems
+    )
+    .into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    fn temp_path(name: &str) -> String {
+        let dir = env::temp_dir().join(format!("boulder_dash_generator_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir.join(name).to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn same_seed_same_cave() {
+        let args = Arguments::default();
+        assert_eq!(fill(&args, 42), fill(&args, 42));
+        assert_ne!(fill(&args, 42), fill(&args, 43));
+    }
+
+    // Rocks and walls block the way, dirt doesn't
+    #[test]
+    fn reachable() {
+        let level = Level::new("p*+O+\n%%%%%\n+    \n").unwrap();
+        assert_eq!(reachable_gems(&level), 1);
+    }
+
+    #[test]
+    fn gems_are_reachable() {
+        let path = temp_path("cave");
+        let args = Arguments {
+            level_paths: vec![path.clone()],
+            seed: Some(5),
+            gems: 20,
+            ..Default::default()
+        };
+        run(&args).unwrap();
+
+        let level = Level::new(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert!(reachable_gems(&level) >= 20);
+        assert_eq!(level.get_players().iter().flatten().count(), 1);
+    }
+
+    #[test]
+    fn quota_too_big() {
+        let path = temp_path("tiny");
+        let args = Arguments {
+            level_paths: vec![path.clone()],
+            cave_size: (3, 3),
+            gems: 50,
+            ..Default::default()
+        };
+        assert!(run(&args).is_err());
+        assert!(!Path::new(&path).exists());
+    }
+}
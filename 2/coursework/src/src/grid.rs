@@ -0,0 +1,177 @@
+use crate::{direction::Direction, objects::Object, Point};
+
+// A rectangular matrix of objects, surrounded by an implicit wall
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Grid {
+    width: usize,
+    rows: Vec<Vec<Object>>,
+}
+
+impl Grid {
+    // Shorter rows are padded with the void
+    pub fn new(mut rows: Vec<Vec<Object>>) -> Self {
+        let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+        for row in &mut rows {
+            row.resize_with(width, Object::default);
+        }
+
+        Self { width, rows }
+    }
+
+    pub const fn get_width(&self) -> usize {
+        self.width
+    }
+    pub fn get_height(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn contains(&self, (x, y): Point) -> bool {
+        x < self.width && y < self.rows.len()
+    }
+    pub fn get(&self, (x, y): Point) -> Option<&Object> {
+        self.rows.get(y)?.get(x)
+    }
+    pub fn get_or_border(&self, point: Point) -> &Object {
+        self.get(point).unwrap_or(&Object::BORDER)
+    }
+
+    // Points outside of the grid are ignored
+    pub fn set(&mut self, (x, y): Point, object: Object) {
+        if let Some(cell) = self.rows.get_mut(y).and_then(|row| row.get_mut(x)) {
+            *cell = object;
+        }
+    }
+    pub fn take(&mut self, (x, y): Point) -> Object {
+        self.rows
+            .get_mut(y)
+            .and_then(|row| row.get_mut(x))
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+
+    // New cells are filled with the void
+    pub fn resize(&mut self, width: usize, height: usize) {
+        self.width = width;
+        self.rows.resize_with(height, Vec::new);
+        for row in &mut self.rows {
+            row.resize_with(width, Object::default);
+        }
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[Object]> {
+        self.rows.iter().map(Vec::as_slice)
+    }
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        let width = self.width;
+        (0..self.rows.len()).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    // None when the neighbour is outside of the grid
+    pub fn neighbour(&self, (x, y): Point, direction: Direction) -> Option<Point> {
+        let point = match direction {
+            Direction::Up => (x, y.checked_sub(1)?),
+            Direction::Down => (x, y + 1),
+            Direction::Left => (x.checked_sub(1)?, y),
+            Direction::Right => (x + 1, y),
+        };
+
+        self.contains(point).then_some(point)
+    }
+    // Leaving one side brings the point in on the other
+    pub fn wrapping_neighbour(&self, (x, y): Point, direction: Direction) -> Option<Point> {
+        let (width, height) = (self.get_width(), self.get_height());
+        if !self.contains((x, y)) {
+            return None;
+        }
+
+        Some(match direction {
+            Direction::Up => (x, (y + height - 1) % height),
+            Direction::Down => (x, (y + 1) % height),
+            Direction::Left => ((x + width - 1) % width, y),
+            Direction::Right => ((x + 1) % width, y),
+        })
+    }
+    pub fn neighbours(
+        &self,
+        point: Point,
+        wrap: bool,
+    ) -> impl Iterator<Item = (Direction, Point)> + '_ {
+        [
+            Direction::Up,
+            Direction::Down,
+            Direction::Left,
+            Direction::Right,
+        ]
+        .into_iter()
+        .filter_map(move |direction| {
+            let next = if wrap {
+                self.wrapping_neighbour(point, direction)
+            } else {
+                self.neighbour(point, direction)
+            };
+            Some((direction, next?))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::Labels;
+
+    // A 3x2 grid from the rows of characters, the second one is short
+    fn grid() -> Grid {
+        Grid::new(
+            ["p+O", "#"]
+                .iter()
+                .map(|row| row.chars().map(Object::new).collect())
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn short_rows_are_padded() {
+        let grid = grid();
+        assert_eq!((grid.get_width(), grid.get_height()), (3, 2));
+        assert_eq!(grid.get_or_border((2, 1)).char(), ' ');
+    }
+
+    #[test]
+    fn out_of_range_is_border() {
+        let grid = grid();
+        for point in [(3, 0), (0, 2), (usize::MAX, usize::MAX)] {
+            assert_eq!(grid.get(point), None);
+            assert_eq!(grid.get_or_border(point), &Object::BORDER);
+        }
+    }
+
+    #[test]
+    fn neighbour_at_the_edges() {
+        let grid = grid();
+        assert_eq!(grid.neighbour((0, 0), Direction::Left), None);
+        assert_eq!(grid.neighbour((0, 0), Direction::Up), None);
+        assert_eq!(grid.neighbour((2, 1), Direction::Right), None);
+        assert_eq!(grid.neighbour((2, 1), Direction::Down), None);
+        assert_eq!(grid.neighbour((0, 0), Direction::Right), Some((1, 0)));
+        assert_eq!(grid.neighbour((0, 0), Direction::Down), Some((0, 1)));
+    }
+
+    #[test]
+    fn wrapping_neighbour_at_the_edges() {
+        let grid = grid();
+        assert_eq!(
+            grid.wrapping_neighbour((0, 0), Direction::Left),
+            Some((2, 0))
+        );
+        assert_eq!(
+            grid.wrapping_neighbour((2, 0), Direction::Right),
+            Some((0, 0))
+        );
+        assert_eq!(grid.wrapping_neighbour((1, 0), Direction::Up), Some((1, 1)));
+        assert_eq!(
+            grid.wrapping_neighbour((1, 1), Direction::Down),
+            Some((1, 0))
+        );
+        assert_eq!(grid.wrapping_neighbour((3, 0), Direction::Left), None);
+    }
+}
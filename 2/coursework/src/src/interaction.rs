@@ -1,38 +1,80 @@
 use crate::{
-    args::{Arguments, InteractionMode},
+    args::{Arguments, InteractionMode, ProgramMode},
+    direction::Direction,
+    grid::Grid,
     objects::Object,
     Point,
 };
 use enum_dispatch::enum_dispatch;
-use std::{cmp, error::Error};
+use std::{
+    cmp,
+    error::Error,
+    time::{Duration, Instant},
+};
 
+mod bot;
+mod buffer;
 mod cli;
 mod gui;
+mod recorder;
+mod server;
 mod tui;
 
+use bot::Bot;
+pub use buffer::Buffer;
 use cli::Cli;
 use gui::Gui;
+use recorder::Recorder;
+use server::Server;
 use tui::Tui;
 
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Input {
     Quit,
     Esc,
     Unknown,
     Q,
     R,
+    E,
     W,
     A,
     S,
     D,
+    I,
+    J,
+    K,
+    L,
+    N,
 
     Up,
     Down,
     Left,
     Right,
     Space,
+    Enter,
     Comma,
     Period,
+    PageUp,
+    PageDown,
+    Snap(Box<Input>), // a direction with the modifier held
+}
+
+impl Input {
+    // Index of the player controlled by the key: WASD - first, arrows - second
+    pub fn player(&self) -> usize {
+        match self {
+            Self::Up | Self::Down | Self::Left | Self::Right => 1,
+            Self::Snap(input) => input.player(),
+            _ => 0,
+        }
+    }
+}
+
+// Objects moved by the last tick as (from, to), and the time until the next one
+#[derive(Default)]
+pub struct Motion {
+    pub moves: Vec<(Point, Point)>,
+    pub duration: Duration,
 }
 
 #[enum_dispatch]
@@ -40,11 +82,21 @@ pub enum Mode {
     Gui,
     Tui,
     Cli,
+    Buffer,
+    Server,
+    Bot,
+    Recorder,
 }
 
 #[enum_dispatch(Mode)]
 pub trait Interaction {
     fn get_input(&mut self) -> Input;
+    // Blocks until there is an input or the timeout runs out
+    fn wait_input(&mut self, timeout: Option<Duration>) -> Input;
+    // The time the ticks are scheduled by
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
     fn draw(&mut self, drawable: &mut impl Drawable) -> Result<(), Box<dyn Error>>;
 }
 
@@ -54,10 +106,7 @@ pub trait Drawable {
     }
     fn get_width(&self) -> usize {
         cmp::max(
-            self.get_objects()
-                .iter()
-                .max_by_key(|r| r.len())
-                .map_or(0, Vec::len),
+            self.get_objects().get_width(),
             self.get_status()
                 .lines()
                 .max_by_key(|r| r.len())
@@ -65,18 +114,80 @@ pub trait Drawable {
         )
     }
     fn get_height(&self) -> usize {
-        self.get_objects().len() + self.get_status().lines().count()
+        self.get_objects().get_height() + self.get_status().lines().count()
     }
     fn get_status(&self) -> String;
     fn get_damaged(&mut self) -> Vec<Point>;
-    fn get_objects(&self) -> &Vec<Vec<Object>>;
-    fn get_object(&self, point: Point) -> Option<&Object>;
+    // Taken once, like the damaged cells
+    fn get_motion(&mut self) -> Motion {
+        Motion::default()
+    }
+    fn get_objects(&self) -> &Grid;
+    fn get_object(&self, point: Point) -> Option<&Object> {
+        self.get_objects().get(point)
+    }
+    // The rules of the level, where the rocks fall and whether the edges lead around
+    fn get_gravity(&self) -> Direction {
+        Direction::Down
+    }
+    fn get_wrap(&self) -> bool {
+        false
+    }
+}
+
+// Passes the damaged cells collected by a wrapper mode to the wrapped interaction,
+// not generic to keep the nested wrappers from instantiating `draw` without an end
+struct Damaged<'a> {
+    drawable: &'a mut dyn Drawable,
+    damaged: Vec<Point>,
+}
+
+impl Drawable for Damaged<'_> {
+    fn get_cursor(&self) -> Option<&Point> {
+        self.drawable.get_cursor()
+    }
+    fn get_status(&self) -> String {
+        self.drawable.get_status()
+    }
+    fn get_damaged(&mut self) -> Vec<Point> {
+        std::mem::take(&mut self.damaged)
+    }
+    fn get_motion(&mut self) -> Motion {
+        self.drawable.get_motion()
+    }
+    fn get_objects(&self) -> &Grid {
+        self.drawable.get_objects()
+    }
+    fn get_gravity(&self) -> Direction {
+        self.drawable.get_gravity()
+    }
+    fn get_wrap(&self) -> bool {
+        self.drawable.get_wrap()
+    }
 }
 
 pub fn get_mode(args: &Arguments) -> Result<Mode, String> {
-    Ok(match args.interaction_mode {
+    let mode = match args.interaction_mode {
         InteractionMode::Gui => Gui::new(args.size).map_err(|e| e.to_string())?.into(),
         InteractionMode::Tui => Tui::new().into(),
         InteractionMode::Cli => Cli::new().into(),
+    };
+    let mode = match &args.record_path {
+        Some(path) => Recorder::new(path, mode, args.size)
+            .map_err(|e| format!("{path}: {e}"))?
+            .into(),
+        None => mode,
+    };
+    // Spectators watch the bot play
+    let mode = match args.program_mode {
+        ProgramMode::Game if args.demo => Bot::new(mode).into(),
+        _ => mode,
+    };
+
+    Ok(match (&args.program_mode, &args.address) {
+        (ProgramMode::Game, Some(address)) => Server::new(address, mode)
+            .map_err(|e| e.to_string())?
+            .into(),
+        _ => mode,
     })
 }
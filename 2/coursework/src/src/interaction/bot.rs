@@ -0,0 +1,150 @@
+use super::{Damaged, Drawable, Input, Interaction, Mode};
+use crate::{direction::Direction, grid::Grid, objects::Properties, Point};
+use std::{
+    collections::{HashMap, VecDeque},
+    error::Error,
+    time::{Duration, Instant},
+};
+
+// How long the finished level stays on the screen
+const RESTART_DELAY: Duration = Duration::from_secs(3);
+
+// Plays the game by itself, collecting the nearest gems
+pub struct Bot {
+    inner: Box<Mode>,
+    next: Option<Input>,         // a single move for every changed frame
+    restart_at: Option<Instant>, // set when there is nothing left to do
+    started: bool,               // the main menu is answered, the others are left to the user
+}
+
+impl Bot {
+    pub fn new(inner: Mode) -> Self {
+        Self {
+            inner: Box::new(inner),
+            next: None,
+            restart_at: None,
+            started: false,
+        }
+    }
+
+    fn plan(drawable: &impl Drawable) -> Option<Input> {
+        let (grid, wrap) = (drawable.get_objects(), drawable.get_wrap());
+        let player = grid.points().find(|&p| grid.get_or_border(p).player())?;
+
+        let passable = |point: Point| {
+            let obj = grid.get_or_border(point);
+            obj.placeholder() || obj.can_be_broken()
+        };
+        let above = drawable.get_gravity().opposite();
+        let under_rock = |point: Point| {
+            let next = if wrap {
+                grid.wrapping_neighbour(point, above)
+            } else {
+                grid.neighbour(point, above)
+            };
+            next.is_some_and(|above| grid.get_or_border(above).can_be_moved())
+        };
+        let gem = |point: Point| grid.get_or_border(point).collectible();
+
+        // Gems under the rocks are only collected when nothing else is reachable
+        let direction = first_step(grid, wrap, player, |p| passable(p) && !under_rock(p), gem)
+            .or_else(|| first_step(grid, wrap, player, passable, gem))?;
+
+        Some(match direction {
+            Direction::Up => Input::W,
+            Direction::Down => Input::S,
+            Direction::Left => Input::A,
+            Direction::Right => Input::D,
+        })
+    }
+}
+
+// Breadth-first search, returns the direction of the first step on the shortest path
+fn first_step(
+    grid: &Grid,
+    wrap: bool,
+    from: Point,
+    passable: impl Fn(Point) -> bool,
+    target: impl Fn(Point) -> bool,
+) -> Option<Direction> {
+    let mut first = HashMap::new();
+    let mut queue = VecDeque::new();
+
+    for (direction, point) in grid.neighbours(from, wrap) {
+        if passable(point) {
+            first.insert(point, direction);
+            queue.push_back(point);
+        }
+    }
+
+    while let Some(point) = queue.pop_front() {
+        let direction = first[&point];
+        if target(point) {
+            return Some(direction);
+        }
+
+        for (_, next) in grid.neighbours(point, wrap) {
+            if next != from && passable(next) && !first.contains_key(&next) {
+                first.insert(next, direction);
+                queue.push_back(next);
+            }
+        }
+    }
+
+    None
+}
+
+impl Interaction for Bot {
+    // Keys pressed by the user come before the moves of the bot
+    fn get_input(&mut self) -> Input {
+        match self.inner.get_input() {
+            Input::Unknown => self.next.take().unwrap_or(Input::Unknown),
+            input => {
+                self.next = None;
+                input
+            }
+        }
+    }
+
+    fn wait_input(&mut self, timeout: Option<Duration>) -> Input {
+        if self.next.is_some() {
+            return self.get_input();
+        }
+
+        // The level is over or the player is stuck
+        let Some(restart_at) = self.restart_at else {
+            return self.inner.wait_input(timeout);
+        };
+        let left = restart_at.saturating_duration_since(self.inner.now());
+        if left.is_zero() {
+            self.restart_at = None;
+            return Input::R;
+        }
+        self.inner
+            .wait_input(Some(timeout.map_or(left, |t| t.min(left))))
+    }
+    fn now(&self) -> Instant {
+        self.inner.now()
+    }
+
+    fn draw(&mut self, drawable: &mut impl Drawable) -> Result<(), Box<dyn Error>> {
+        let damaged = drawable.get_damaged();
+        // Menus have no level, the first item of the main menu starts the game
+        if drawable.get_objects().get_height() == 0 {
+            self.next = (!self.started).then_some(Input::Enter);
+            self.started = true;
+            self.restart_at = None;
+        } else if !damaged.is_empty() {
+            // Nothing has changed since the last move otherwise
+            self.next = Self::plan(drawable);
+            self.restart_at = match self.next {
+                Some(_) => None,
+                None => self
+                    .restart_at
+                    .or_else(|| Some(self.inner.now() + RESTART_DELAY)),
+            };
+        }
+
+        self.inner.draw(&mut Damaged { drawable, damaged })
+    }
+}
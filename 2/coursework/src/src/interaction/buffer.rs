@@ -0,0 +1,78 @@
+use super::{Drawable, Input, Interaction};
+use crate::objects::Labels;
+use std::{
+    collections::VecDeque,
+    error::Error,
+    time::{Duration, Instant},
+};
+
+pub struct Buffer {
+    grid: Vec<Vec<char>>,
+    frames: Vec<String>,
+    input: VecDeque<Input>,
+    clock: Instant, // moved by the timeouts instead of the wall clock
+}
+
+impl Buffer {
+    // Games start at the main menu, so their scripts begin with Enter
+    pub fn new(input: impl IntoIterator<Item = Input>) -> Self {
+        Self {
+            grid: vec![],
+            frames: vec![],
+            input: input.into_iter().collect(),
+            clock: Instant::now(),
+        }
+    }
+
+    pub fn push_input(&mut self, input: Input) {
+        self.input.push_back(input);
+    }
+
+    pub fn get_frames(&self) -> &[String] {
+        &self.frames
+    }
+    pub fn get_frame(&self) -> Option<&str> {
+        self.frames.last().map(String::as_str)
+    }
+}
+
+impl Interaction for Buffer {
+    // Quit once the script runs out, so the program always finishes
+    fn get_input(&mut self) -> Input {
+        self.input.pop_front().unwrap_or(Input::Quit)
+    }
+    // Every scripted input comes once the timeout is over, without waiting for it
+    fn wait_input(&mut self, timeout: Option<Duration>) -> Input {
+        self.clock += timeout.unwrap_or_default();
+        self.get_input()
+    }
+    fn now(&self) -> Instant {
+        self.clock
+    }
+
+    fn draw(&mut self, drawable: &mut impl Drawable) -> Result<(), Box<dyn Error>> {
+        // Follow the shape of the drawable, the damaged buffer doesn't track removed cells
+        let objects = drawable.get_objects();
+        self.grid.resize_with(objects.get_height(), Vec::new);
+        for row in &mut self.grid {
+            row.resize(objects.get_width(), ' ');
+        }
+
+        for (x, y) in drawable.get_damaged() {
+            if let Some(obj) = drawable.get_object((x, y)) {
+                self.grid[y][x] = obj.char();
+            }
+        }
+
+        let mut frame = String::new();
+        for row in &self.grid {
+            frame.extend(row);
+            frame.push('\n');
+        }
+        frame.push('\n');
+        frame += &drawable.get_status();
+
+        self.frames.push(frame);
+        Ok(())
+    }
+}
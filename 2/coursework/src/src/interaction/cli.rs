@@ -1,6 +1,6 @@
 use super::{Drawable, Input, Interaction, Tui};
 use crate::objects::Labels;
-use std::error::Error;
+use std::{error::Error, time::Duration};
 
 pub struct Cli {
     tui: Tui,
@@ -25,6 +25,9 @@ impl Interaction for Cli {
     fn get_input(&mut self) -> Input {
         self.tui.get_input()
     }
+    fn wait_input(&mut self, timeout: Option<Duration>) -> Input {
+        self.tui.wait_input(timeout)
+    }
 
     fn draw(&mut self, drawable: &mut impl Drawable) -> Result<(), Box<dyn Error>> {
         let term = self.tui.get_term();
@@ -36,7 +39,7 @@ impl Interaction for Cli {
             }
         }
 
-        term.move_cursor_to(0, drawable.get_objects().len())?;
+        term.move_cursor_to(0, drawable.get_objects().get_height())?;
         term.clear_to_end_of_screen()?;
         term.move_cursor_down(1)?;
         term.write_line(&drawable.get_status())?;
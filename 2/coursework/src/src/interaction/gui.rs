@@ -1,17 +1,38 @@
-use super::{Drawable, Input, Interaction};
-use crate::objects::Labels;
+use super::{Drawable, Input, Interaction, Motion};
+use crate::{direction::Direction, objects::Labels, Point};
 use sdl2::{
     event::Event,
     image::LoadTexture,
-    keyboard::Keycode,
+    keyboard::{Keycode, Mod},
     pixels::Color,
     rect::Rect,
-    render::{Canvas, TextureCreator, TextureQuery},
+    render::{Canvas, Texture, TextureCreator, TextureQuery},
+    surface::Surface,
     ttf::Sdl2TtfContext,
     video::{Window, WindowContext},
     EventPump, IntegerOrSdlError,
 };
-use std::{collections::BTreeMap, error::Error, fs};
+use std::{
+    cmp,
+    collections::{BTreeMap, HashSet},
+    error::Error,
+    fs,
+    time::{Duration, Instant},
+};
+
+// About the refresh rate of the most displays
+const FRAME: Duration = Duration::from_millis(16);
+
+// Sprites sliding from their old cells to the new ones until the next tick
+struct Animation {
+    start: Instant,
+    duration: Duration,
+    size: (usize, usize), // of the level, another one means the level was replaced
+    sliding: Vec<(String, Point, Point)>,
+    cells: Vec<(Point, String, bool)>, // the whole level, true for the destinations
+    status: Vec<Surface<'static>>,
+    cursor: Option<Point>,
+}
 
 pub struct Gui {
     scale: u32,
@@ -19,13 +40,13 @@ pub struct Gui {
     event_pump: EventPump,
     ttf_context: Sdl2TtfContext,
     texture_creator: TextureCreator<WindowContext>,
-    texture_cache: BTreeMap<String, Box<[u8]>>,
+    textures: BTreeMap<String, Texture>, // the sprites by their names, destroyed with the window
+    animation: Option<Animation>,
 }
 
 impl Gui {
     pub fn new(scale: u32) -> Result<Self, Box<dyn Error>> {
         let sdl_context = sdl2::init()?;
-        let ttf_context = sdl2::ttf::init()?;
 
         let canvas = sdl_context
             .video()?
@@ -38,22 +59,159 @@ impl Gui {
         let event_pump = sdl_context.event_pump()?;
         let texture_creator = canvas.texture_creator();
 
-        let mut texture_cache = BTreeMap::new();
+        let mut textures = BTreeMap::new();
         for path in fs::read_dir("assets/sprites/")?.filter_map(Result::ok) {
-            let contents = fs::read(path.path())?.into_boxed_slice();
-            texture_cache.insert(path.file_name().into_string().expect("str path"), contents);
+            let texture = texture_creator.load_texture_bytes(&fs::read(path.path())?)?;
+            textures.insert(path.file_name().into_string().expect("str path"), texture);
         }
 
         Ok(Self {
             scale,
             canvas,
             event_pump,
-            ttf_context,
+            ttf_context: sdl2::ttf::init()?,
             texture_creator,
-            texture_cache,
+            textures,
+            animation: None,
         })
     }
 
+    fn draw_sprite(&mut self, sprite: &str, (x, y): (i32, i32)) -> Result<(), Box<dyn Error>> {
+        let texture = self
+            .textures
+            .get(sprite)
+            .unwrap_or(&self.textures["unknown"]);
+        self.canvas
+            .copy(texture, None, Rect::new(x, y, self.scale, self.scale))?;
+        Ok(())
+    }
+
+    fn render_status(&self, status: &str) -> Result<Vec<Surface<'static>>, Box<dyn Error>> {
+        let font = self
+            .ttf_context
+            .load_font("assets/font.ttf", u16::try_from(self.scale)?)?;
+        let mut lines = vec![];
+        for line in status.lines() {
+            lines.push(font.render(line).blended(Color::RGB(200, 255, 0))?);
+        }
+        Ok(lines)
+    }
+
+    // Line by line under the level
+    fn draw_status(
+        &mut self,
+        lines: &[Surface],
+        level_height: usize,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut top = u32::try_from(level_height)? * self.scale;
+        for surface in lines {
+            let texture = self.texture_creator.create_texture_from_surface(surface)?;
+            let TextureQuery { width, height, .. } = texture.query();
+            let rect = Rect::new(0, i32::try_from(top)?, width, height);
+            let copied = self.canvas.copy(&texture, None, rect);
+            // The canvas that the texture belongs to is alive
+            unsafe { texture.destroy() };
+            copied?;
+            top += self.scale;
+        }
+        Ok(())
+    }
+
+    fn draw_cursor(&mut self, (x, y): Point) -> Result<(), Box<dyn Error>> {
+        self.canvas.set_draw_color(Color::RGB(0, 255, 0));
+        for i in 0..self.scale / 6 {
+            self.canvas.draw_rect(Rect::new(
+                i32::try_from(x)? * i32::try_from(self.scale)? + i32::try_from(i)?,
+                i32::try_from(y)? * i32::try_from(self.scale)? + i32::try_from(i)?,
+                self.scale.saturating_sub(2 * i),
+                self.scale.saturating_sub(2 * i),
+            ))?;
+        }
+        self.canvas.set_draw_color(Color::BLACK);
+        Ok(())
+    }
+
+    fn present(&mut self) {
+        // TODO: why 3 calls?
+        self.canvas.present();
+        self.canvas.present();
+        self.canvas.present();
+    }
+
+    fn start_animation(
+        &mut self,
+        drawable: &impl Drawable,
+        motion: Motion,
+    ) -> Result<(), Box<dyn Error>> {
+        let grid = drawable.get_objects();
+        let sliding: Vec<(String, Point, Point)> = motion
+            .moves
+            .into_iter()
+            .filter_map(|(from, to)| Some((grid.get(to)?.sprite(), from, to)))
+            .collect();
+        let destinations: HashSet<Point> = sliding.iter().map(|&(_, _, to)| to).collect();
+
+        self.animation = Some(Animation {
+            start: Instant::now(),
+            duration: motion.duration,
+            size: (grid.get_width(), grid.get_height()),
+            sliding,
+            cells: grid
+                .points()
+                .filter_map(|point| {
+                    let sprite = grid.get(point)?.sprite();
+                    Some((point, sprite, destinations.contains(&point)))
+                })
+                .collect(),
+            status: self.render_status(&drawable.get_status())?,
+            cursor: drawable.get_cursor().copied(),
+        });
+        Ok(())
+    }
+
+    // Every frame is drawn whole, the back buffer isn't kept after presenting.
+    // The final positions are drawn once the time is up
+    fn draw_animation(&mut self) -> Result<(), Box<dyn Error>> {
+        let Some(animation) = self.animation.take() else {
+            return Ok(());
+        };
+        let duration = animation.duration.as_millis().max(1);
+        let progress = i32::try_from(
+            cmp::min(animation.start.elapsed().as_millis(), duration) * 1000 / duration,
+        )?;
+        let scale = i32::try_from(self.scale)?;
+        let position = |(x, y): Point| -> Result<(i32, i32), Box<dyn Error>> {
+            Ok((i32::try_from(x)? * scale, i32::try_from(y)? * scale))
+        };
+
+        self.canvas.set_draw_color(Color::BLACK);
+        self.canvas.clear();
+        for (point, sprite, destination) in &animation.cells {
+            if progress == 1000 || !destination {
+                self.draw_sprite(sprite, position(*point)?)?;
+            }
+        }
+
+        if progress < 1000 {
+            for (sprite, from, to) in &animation.sliding {
+                let (from, to) = (position(*from)?, position(*to)?);
+                let x = from.0 + (to.0 - from.0) * progress / 1000;
+                let y = from.1 + (to.1 - from.1) * progress / 1000;
+                self.draw_sprite(sprite, (x, y))?;
+            }
+        }
+
+        self.draw_status(&animation.status, animation.size.1)?;
+        if let Some(cursor) = animation.cursor {
+            self.draw_cursor(cursor)?;
+        }
+
+        if progress < 1000 {
+            self.animation = Some(animation);
+        }
+        Ok(())
+    }
+
     fn resize_window(&mut self, (width, height): (u32, u32)) -> Result<(), IntegerOrSdlError> {
         self.canvas.window_mut().set_minimum_size(width, height)?;
         self.canvas.window_mut().set_maximum_size(width, height)
@@ -62,42 +220,117 @@ impl Gui {
     }
 }
 
+impl Gui {
+    fn map_event(event: Event) -> Option<Input> {
+        match event {
+            Event::Quit { .. } => Some(Input::Quit),
+
+            Event::KeyDown {
+                keycode: Some(key),
+                keymod,
+                ..
+            } => {
+                let input = match key {
+                    Keycode::Escape => Some(Input::Esc),
+                    Keycode::Space => Some(Input::Space),
+                    Keycode::Return => Some(Input::Enter),
+                    Keycode::Comma => Some(Input::Comma),
+                    Keycode::Period => Some(Input::Period),
+                    Keycode::Q => Some(Input::Q),
+                    Keycode::P => Some(Input::R),
+                    Keycode::F => Some(Input::E),
+
+                    Keycode::W => Some(Input::W),
+                    Keycode::A => Some(Input::A),
+                    Keycode::R => Some(Input::S),
+                    Keycode::S => Some(Input::D),
+                    Keycode::U => Some(Input::I),
+                    Keycode::N => Some(Input::J),
+                    Keycode::E => Some(Input::K),
+                    Keycode::I => Some(Input::L),
+                    Keycode::K => Some(Input::N),
+                    Keycode::Up => Some(Input::Up),
+                    Keycode::Down => Some(Input::Down),
+                    Keycode::Left => Some(Input::Left),
+                    Keycode::Right => Some(Input::Right),
+                    Keycode::PageUp => Some(Input::PageUp),
+                    Keycode::PageDown => Some(Input::PageDown),
+                    _ => None,
+                }?;
+
+                // Directions with Shift snap
+                let shift = keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD);
+                if shift && Direction::try_from(input.clone()).is_ok() {
+                    Some(Input::Snap(Box::new(input)))
+                } else {
+                    Some(input)
+                }
+            }
+
+            _ => None,
+        }
+    }
+}
+
+impl Drop for Gui {
+    fn drop(&mut self) {
+        for texture in std::mem::take(&mut self.textures).into_values() {
+            // Before the canvas, which is dropped after this
+            unsafe { texture.destroy() };
+        }
+    }
+}
+
 impl Interaction for Gui {
     fn get_input(&mut self) -> Input {
         let mut input = Input::Unknown;
 
         while let Some(event) = self.event_pump.poll_event() {
-            input = match event {
-                Event::Quit { .. } => return Input::Quit,
-
-                Event::KeyDown {
-                    keycode: Some(key), ..
-                } => match key {
-                    Keycode::Escape => Input::Esc,
-                    Keycode::Space => Input::Space,
-                    Keycode::Comma => Input::Comma,
-                    Keycode::Period => Input::Period,
-                    Keycode::Q => Input::Q,
-                    Keycode::P => Input::R,
-
-                    Keycode::W => Input::W,
-                    Keycode::A => Input::A,
-                    Keycode::R => Input::S,
-                    Keycode::S => Input::D,
-                    Keycode::Up => Input::Up,
-                    Keycode::Down => Input::Down,
-                    Keycode::Left => Input::Left,
-                    Keycode::Right => Input::Right,
-                    _ => input,
-                },
-
-                _ => input,
+            match Self::map_event(event) {
+                Some(Input::Quit) => return Input::Quit,
+                Some(new_input) => input = new_input,
+                None => (),
             }
         }
 
         input
     }
 
+    fn wait_input(&mut self, timeout: Option<Duration>) -> Input {
+        // Frames are drawn between the events until the animation is over
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        while self.animation.is_some() {
+            if self.draw_animation().is_err() {
+                self.animation = None;
+            }
+            self.present();
+
+            let left = deadline.map_or(FRAME, |d| d.saturating_duration_since(Instant::now()));
+            let ms = u32::try_from(cmp::min(left, FRAME).as_millis()).unwrap_or(u32::MAX);
+            if let Some(input) = self
+                .event_pump
+                .wait_event_timeout(ms)
+                .and_then(Self::map_event)
+            {
+                return input;
+            }
+            if left <= FRAME && deadline.is_some() {
+                return Input::Unknown;
+            }
+        }
+        let timeout = deadline.map(|d| d.saturating_duration_since(Instant::now()));
+
+        let event = match timeout {
+            Some(timeout) => {
+                let ms = u32::try_from(timeout.as_millis()).unwrap_or(u32::MAX);
+                self.event_pump.wait_event_timeout(ms)
+            }
+            None => Some(self.event_pump.wait_event()),
+        };
+
+        event.and_then(Self::map_event).unwrap_or(Input::Unknown)
+    }
+
     fn draw(&mut self, drawable: &mut impl Drawable) -> Result<(), Box<dyn Error>> {
         self.canvas.set_draw_color(Color::BLACK);
         // Redraw objects using the damaged buffer
@@ -117,12 +350,19 @@ impl Interaction for Gui {
             self.canvas.clear(); // clear the artifacts after resize
 
             // Redraw all objects
-            objects_to_redraw = drawable
-                .get_objects()
-                .iter()
-                .enumerate()
-                .flat_map(|(y, row)| (0..row.len()).map(move |x| (x, y)))
-                .collect();
+            objects_to_redraw = drawable.get_objects().points().collect();
+        }
+
+        // Another tick replaces the animation, any other change of the level ends it
+        let motion = drawable.get_motion();
+        let grid = drawable.get_objects();
+        if let Some(animation) = self.animation.as_ref().filter(|animation| {
+            !motion.moves.is_empty()
+                || !objects_to_redraw.is_empty()
+                || animation.size != (grid.get_width(), grid.get_height())
+        }) {
+            objects_to_redraw.extend(animation.cells.iter().map(|(point, ..)| *point));
+            self.animation = None;
         }
 
         // OBJECTS
@@ -137,18 +377,21 @@ impl Interaction for Gui {
             self.canvas.fill_rect(rect)?; // clear the old artifacts
 
             if let Some(obj) = drawable.get_object((x, y)) {
-                let bytes = &self.texture_cache[&obj.name()];
-                let texture = self.texture_creator.load_texture_bytes(bytes)?;
-                self.canvas.copy(&texture, None, rect)?;
+                self.draw_sprite(&obj.sprite(), (rect.x(), rect.y()))?;
             }
         }
 
+        if !motion.moves.is_empty() {
+            self.start_animation(drawable, motion)?;
+            self.draw_animation()?;
+            self.present();
+            return Ok(());
+        }
+
         // STATUS
 
-        let font = self
-            .ttf_context
-            .load_font("assets/font.ttf", u16::try_from(self.scale)?)?;
-        let mut level_bottom = u32::try_from(drawable.get_objects().len())? * self.scale;
+        let level_height = drawable.get_objects().get_height();
+        let level_bottom = u32::try_from(level_height)? * self.scale;
 
         // Clear the bottom of the screen
         self.canvas.fill_rect(Rect::new(
@@ -157,42 +400,17 @@ impl Interaction for Gui {
             drawable_size.0,
             drawable_size.1.saturating_sub(level_bottom),
         ))?;
-
-        // Draw the status line by line
-        for line in drawable.get_status().lines() {
-            let font_surface = font.render(line).blended(Color::RGB(200, 255, 0))?;
-            let font_texture = self
-                .texture_creator
-                .create_texture_from_surface(&font_surface)?;
-
-            let TextureQuery { width, height, .. } = font_texture.query();
-            let rect = Rect::new(0, i32::try_from(level_bottom)?, width, height);
-            self.canvas.copy(&font_texture, None, rect)?;
-
-            level_bottom += self.scale;
-        }
+        let status = self.render_status(&drawable.get_status())?;
+        self.draw_status(&status, level_height)?;
 
         // CURSOR
 
-        if let Some(&(x, y)) = drawable.get_cursor() {
-            self.canvas.set_draw_color(Color::RGB(0, 255, 0));
-
-            for i in 0..self.scale / 6 {
-                self.canvas.draw_rect(Rect::new(
-                    i32::try_from(x)? * i32::try_from(self.scale)? + i32::try_from(i)?,
-                    i32::try_from(y)? * i32::try_from(self.scale)? + i32::try_from(i)?,
-                    self.scale.saturating_sub(2 * i),
-                    self.scale.saturating_sub(2 * i),
-                ))?;
-            }
+        if let Some(&cursor) = drawable.get_cursor() {
+            self.draw_cursor(cursor)?;
         }
 
         // Displaying the backbuffer
-
-        // TODO: why 3 calls?
-        self.canvas.present();
-        self.canvas.present();
-        self.canvas.present();
+        self.present();
 
         Ok(())
     }
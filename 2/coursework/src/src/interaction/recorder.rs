@@ -0,0 +1,243 @@
+use super::{Drawable, Input, Interaction, Mode};
+use crate::{export, game::stats::json_string, objects::Labels};
+use console::Term;
+use std::{
+    borrow::Cow,
+    error::Error,
+    fs::File,
+    io::{self, BufWriter, Write},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+// The last frame of the GIF stays on the screen for a while
+const LAST_FRAME_DELAY: u16 = 200; // in centiseconds
+
+// A frame of the GIF, kept as sprite indices until the recording is over
+struct Snapshot {
+    time: Duration,
+    width: usize,
+    cells: Vec<usize>,
+}
+
+enum Output {
+    // asciicast v2, written as the frames come
+    Cast {
+        file: BufWriter<File>,
+        emoji: bool,
+        last_frame: String,
+    },
+    // The size of the GIF is only known in the end
+    Gif {
+        path: String,
+        scale: u32,
+        sprites: Vec<String>,
+        snapshots: Vec<Snapshot>,
+    },
+}
+
+// Saves every drawn frame, the wrapped interaction is used as usual
+pub struct Recorder {
+    inner: Box<Mode>,
+    start: Instant,
+    output: Output,
+}
+
+impl Recorder {
+    pub fn new(path: &str, inner: Mode, scale: u32) -> io::Result<Self> {
+        let output = match inner {
+            Mode::Gui(_) => Output::Gif {
+                path: path.to_string(),
+                scale,
+                sprites: vec![],
+                snapshots: vec![],
+            },
+            _ => {
+                let mut file = BufWriter::new(File::create(path)?);
+                let (rows, columns) = Term::stdout().size();
+                let timestamp = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |d| d.as_secs());
+                writeln!(
+                    file,
+                    "{{\"version\": 2, \"width\": {columns}, \"height\": {rows}, \"timestamp\": {timestamp}}}"
+                )?;
+
+                Output::Cast {
+                    file,
+                    emoji: matches!(inner, Mode::Tui(_)),
+                    last_frame: String::new(),
+                }
+            }
+        };
+
+        Ok(Self {
+            inner: Box::new(inner),
+            start: Instant::now(),
+            output,
+        })
+    }
+
+    fn record(&mut self, drawable: &impl Drawable) -> io::Result<()> {
+        let time = self.start.elapsed();
+        let grid = drawable.get_objects();
+
+        match &mut self.output {
+            Output::Cast {
+                file,
+                emoji,
+                last_frame,
+            } => {
+                // The whole screen is redrawn, like in the TUI
+                let mut frame = String::from("\x1b[2J\x1b[H");
+                for row in grid.rows() {
+                    frame.extend(
+                        row.iter()
+                            .map(|o| if *emoji { o.emoji() } else { o.char() }),
+                    );
+                    frame += "\r\n";
+                }
+                frame += "\r\n";
+                frame += &drawable.get_status().replace('\n', "\r\n");
+
+                if frame != *last_frame {
+                    let data = json_string(&frame);
+                    writeln!(file, "[{:.6}, \"o\", {data}]", time.as_secs_f64())?;
+                    file.flush()?;
+                    *last_frame = frame;
+                }
+            }
+            Output::Gif {
+                sprites, snapshots, ..
+            } => {
+                // Menus have nothing to show
+                if grid.get_height() == 0 {
+                    return Ok(());
+                }
+
+                let cells = grid.points().map(|point| {
+                    let sprite = grid.get_or_border(point).sprite();
+                    sprites
+                        .iter()
+                        .position(|s| *s == sprite)
+                        .unwrap_or_else(|| {
+                            sprites.push(sprite);
+                            sprites.len() - 1
+                        })
+                });
+                let snapshot = Snapshot {
+                    time,
+                    width: grid.get_width(),
+                    cells: cells.collect(),
+                };
+
+                let last = snapshots.last();
+                if !last.is_some_and(|l| l.width == snapshot.width && l.cells == snapshot.cells) {
+                    snapshots.push(snapshot);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// Colors are reduced to a 6x6x6 cube, the same palette fits every frame
+fn palette_index(rgb: &[u8]) -> u8 {
+    let level = |c: u8| (u16::from(c) + 25) / 51;
+    u8::try_from(level(rgb[0]) * 36 + level(rgb[1]) * 6 + level(rgb[2])).unwrap_or(0)
+}
+
+fn write_gif(
+    path: &str,
+    scale: u32,
+    sprites: &[String],
+    snapshots: &[Snapshot],
+) -> Result<(), Box<dyn Error>> {
+    let cell = usize::try_from(scale)?;
+
+    // Sprites as palette indices, row by row
+    let mut tiles = vec![];
+    for sprite in sprites {
+        let surface = export::load_sprite(sprite, scale)?;
+        let pitch = usize::try_from(surface.pitch())?;
+        let tile = surface.with_lock(|pixels| {
+            (0..cell * cell)
+                .map(|i| palette_index(&pixels[i / cell * pitch + i % cell * 4..]))
+                .collect::<Vec<_>>()
+        });
+        tiles.push(tile);
+    }
+
+    let columns = snapshots.iter().map(|s| s.width).max().unwrap_or(0);
+    let rows = snapshots
+        .iter()
+        .map(|s| s.cells.len() / s.width.max(1))
+        .max()
+        .unwrap_or(0);
+    let (width, height) = (u16::try_from(columns * cell)?, u16::try_from(rows * cell)?);
+
+    let palette: Vec<u8> = (0..216)
+        .flat_map(|i: u16| [i / 36, i / 6 % 6, i % 6].map(|c| u8::try_from(c * 51).unwrap_or(0)))
+        .collect();
+    let mut encoder = gif::Encoder::new(File::create(path)?, width, height, &palette)?;
+    encoder.set_repeat(gif::Repeat::Infinite)?;
+
+    for (idx, snapshot) in snapshots.iter().enumerate() {
+        // Palette index 0 is black
+        let mut buffer = vec![0; usize::from(width) * usize::from(height)];
+        for (i, &sprite) in snapshot.cells.iter().enumerate() {
+            let (x, y) = (i % snapshot.width, i / snapshot.width);
+            for (row, line) in tiles[sprite].chunks(cell).enumerate() {
+                let start = (y * cell + row) * usize::from(width) + x * cell;
+                buffer[start..start + cell].copy_from_slice(line);
+            }
+        }
+
+        let delay = snapshots.get(idx + 1).map_or(LAST_FRAME_DELAY, |next| {
+            let centiseconds = (next.time - snapshot.time).as_millis() / 10;
+            u16::try_from(centiseconds).unwrap_or(u16::MAX)
+        });
+        encoder.write_frame(&gif::Frame {
+            width,
+            height,
+            delay,
+            buffer: Cow::Owned(buffer),
+            ..Default::default()
+        })?;
+    }
+
+    Ok(())
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        if let Output::Gif {
+            path,
+            scale,
+            sprites,
+            snapshots,
+        } = &self.output
+        {
+            if let Err(err) = write_gif(path, *scale, sprites, snapshots) {
+                eprintln!("Can't write the recording to {path}: {err}");
+            }
+        }
+    }
+}
+
+impl Interaction for Recorder {
+    fn get_input(&mut self) -> Input {
+        self.inner.get_input()
+    }
+    fn wait_input(&mut self, timeout: Option<Duration>) -> Input {
+        self.inner.wait_input(timeout)
+    }
+    fn now(&self) -> Instant {
+        self.inner.now()
+    }
+
+    fn draw(&mut self, drawable: &mut impl Drawable) -> Result<(), Box<dyn Error>> {
+        self.record(drawable)?;
+        self.inner.draw(drawable)
+    }
+}
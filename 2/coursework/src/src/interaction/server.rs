@@ -0,0 +1,144 @@
+use super::{Damaged, Drawable, Input, Interaction, Mode};
+use crate::{objects::Labels, spectator::Frame, Point};
+use std::{
+    error::Error,
+    io,
+    net::{TcpListener, TcpStream},
+    time::{Duration, Instant},
+};
+
+pub struct Server {
+    inner: Box<Mode>,
+    listener: TcpListener,
+    clients: Vec<TcpStream>,
+}
+
+impl Server {
+    pub fn new(address: &str, inner: Mode) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+
+        Ok(Self {
+            inner: Box::new(inner),
+            listener,
+            clients: vec![],
+        })
+    }
+
+    // Failed connections are dropped, the game goes on and the rest are accepted next frame
+    fn accept(&mut self) -> Vec<TcpStream> {
+        let mut new_clients = vec![];
+        while let Ok((stream, _)) = self.listener.accept() {
+            // A slow spectator shouldn't stall the game
+            let configured = stream
+                .set_nonblocking(false)
+                .and_then(|()| stream.set_write_timeout(Some(Duration::from_millis(100))));
+            if configured.is_ok() {
+                new_clients.push(stream);
+            }
+        }
+        new_clients
+    }
+}
+
+impl Interaction for Server {
+    fn get_input(&mut self) -> Input {
+        self.inner.get_input()
+    }
+    fn wait_input(&mut self, timeout: Option<Duration>) -> Input {
+        self.inner.wait_input(timeout)
+    }
+    fn now(&self) -> Instant {
+        self.inner.now()
+    }
+
+    fn draw(&mut self, drawable: &mut impl Drawable) -> Result<(), Box<dyn Error>> {
+        let damaged = drawable.get_damaged();
+        let status = drawable.get_status();
+        let objects = drawable.get_objects();
+        let size = (objects.get_width(), objects.get_height());
+        let cell = |point: Point| Some((point, drawable.get_object(point)?.char()));
+
+        // New spectators get the whole level
+        let new_clients = self.accept();
+        if !new_clients.is_empty() {
+            let cells = drawable.get_objects().points().filter_map(cell).collect();
+            let frame = Frame {
+                size,
+                cells,
+                status: status.clone(),
+            };
+
+            for mut client in new_clients {
+                if frame.write(&mut client).is_ok() {
+                    self.clients.push(client);
+                }
+            }
+        }
+
+        let frame = Frame {
+            size,
+            cells: damaged.iter().copied().filter_map(cell).collect(),
+            status,
+        };
+        self.clients
+            .retain_mut(|client| frame.write(client).is_ok());
+
+        self.inner.draw(&mut Damaged { drawable, damaged })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{grid::Grid, interaction::Buffer, objects::Object};
+    use std::io::BufReader;
+
+    struct Level {
+        grid: Grid,
+        damaged: Vec<Point>,
+    }
+
+    impl Drawable for Level {
+        fn get_status(&self) -> String {
+            "Score: 0\n".to_string()
+        }
+        fn get_damaged(&mut self) -> Vec<Point> {
+            std::mem::take(&mut self.damaged)
+        }
+        fn get_objects(&self) -> &Grid {
+            &self.grid
+        }
+    }
+
+    #[test]
+    fn loopback() {
+        let mut server = Server::new("127.0.0.1:0", Mode::from(Buffer::new([]))).unwrap();
+        let address = server.listener.local_addr().unwrap();
+        let mut level = Level {
+            grid: Grid::new(vec!["#p#".chars().map(Object::new).collect()]),
+            damaged: vec![],
+        };
+
+        let mut reader = BufReader::new(TcpStream::connect(address).unwrap());
+        server.draw(&mut level).unwrap();
+        // The whole level for a new spectator, the damaged cells are sent after it
+        let frame = Frame::read(&mut reader).unwrap().unwrap();
+        assert_eq!(frame.size, (3, 1));
+        assert_eq!(frame.cells, [((0, 0), '#'), ((1, 0), 'p'), ((2, 0), '#')]);
+        assert_eq!(frame.status, "Score: 0\n");
+        assert!(Frame::read(&mut reader).unwrap().unwrap().cells.is_empty());
+
+        level.grid.set((1, 0), Object::default());
+        level.damaged.push((1, 0));
+        server.draw(&mut level).unwrap();
+        let frame = Frame::read(&mut reader).unwrap().unwrap();
+        assert_eq!(frame.cells, [((1, 0), ' ')]);
+
+        // Closed connections are dropped
+        drop(reader);
+        while !server.clients.is_empty() {
+            server.draw(&mut level).unwrap();
+        }
+    }
+}
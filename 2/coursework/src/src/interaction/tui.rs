@@ -1,7 +1,47 @@
 use super::{Drawable, Input, Interaction};
 use crate::objects::Labels;
 use console::{Key, Term};
-use std::{error::Error, sync::mpsc, thread};
+use std::{error::Error, sync::mpsc, thread, time::Duration};
+
+fn map_key(key: Key) -> Input {
+    match key {
+        Key::Escape => Input::Esc,
+        Key::Char(' ') => Input::Space,
+        Key::Enter => Input::Enter,
+        Key::Char(',') => Input::Comma,
+        Key::Char('.') => Input::Period,
+        Key::Char('q') => Input::Q,
+        Key::Char('p') => Input::R,
+        Key::Char('f') => Input::E,
+
+        Key::Char('w') => Input::W,
+        Key::Char('a') => Input::A,
+        Key::Char('r') => Input::S,
+        Key::Char('s') => Input::D,
+        Key::Char('W') => Input::Snap(Box::new(Input::W)),
+        Key::Char('A') => Input::Snap(Box::new(Input::A)),
+        Key::Char('R') => Input::Snap(Box::new(Input::S)),
+        Key::Char('S') => Input::Snap(Box::new(Input::D)),
+        // Terminals don't report Shift with the arrows, the cursor keys stand in for them
+        Key::Char('U') => Input::Snap(Box::new(Input::Up)),
+        Key::Char('N') => Input::Snap(Box::new(Input::Left)),
+        Key::Char('E') => Input::Snap(Box::new(Input::Down)),
+        Key::Char('I') => Input::Snap(Box::new(Input::Right)),
+        Key::Char('u') => Input::I,
+        Key::Char('n') => Input::J,
+        Key::Char('e') => Input::K,
+        Key::Char('i') => Input::L,
+        Key::Char('k') => Input::N,
+        Key::ArrowUp => Input::Up,
+        Key::ArrowDown => Input::Down,
+        Key::ArrowLeft => Input::Left,
+        Key::ArrowRight => Input::Right,
+        Key::PageUp => Input::PageUp,
+        Key::PageDown => Input::PageDown,
+
+        _ => Input::Unknown,
+    }
+}
 
 pub struct Tui {
     term: Term,
@@ -35,33 +75,22 @@ impl Tui {
 
 impl Interaction for Tui {
     fn get_input(&mut self) -> Input {
-        let input = self.input_rx.try_recv();
-        input.map_or(Input::Unknown, |key| match key {
-            Key::Escape => Input::Esc,
-            Key::Char(' ') => Input::Space,
-            Key::Char(',') => Input::Comma,
-            Key::Char('.') => Input::Period,
-            Key::Char('q') => Input::Q,
-            Key::Char('p') => Input::R,
-
-            Key::Char('w') => Input::W,
-            Key::Char('a') => Input::A,
-            Key::Char('r') => Input::S,
-            Key::Char('s') => Input::D,
-            Key::ArrowUp => Input::Up,
-            Key::ArrowDown => Input::Down,
-            Key::ArrowLeft => Input::Left,
-            Key::ArrowRight => Input::Right,
-
-            _ => Input::Unknown,
-        })
+        self.input_rx.try_recv().map_or(Input::Unknown, map_key)
+    }
+
+    fn wait_input(&mut self, timeout: Option<Duration>) -> Input {
+        let key = match timeout {
+            Some(timeout) => self.input_rx.recv_timeout(timeout).ok(),
+            None => self.input_rx.recv().ok(),
+        };
+        key.map_or(Input::Unknown, map_key)
     }
 
     fn draw(&mut self, drawable: &mut impl Drawable) -> Result<(), Box<dyn Error>> {
         self.term.clear_screen()?;
 
         drawable.get_damaged(); // Empty damaged buffer
-        for row in drawable.get_objects() {
+        for row in drawable.get_objects().rows() {
             let mut line = String::new();
             for obj in row {
                 line.push(obj.emoji());
@@ -0,0 +1,154 @@
+use crate::{bdcff, direction::Direction};
+use std::{error::Error, fmt, fs};
+
+// The `@key value` lines at the start of a level file
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Metadata {
+    pub title: Option<String>,       // starts a new level in a pack
+    pub quota: Option<usize>,        // gems to collect, all of them by default
+    pub time: Option<usize>,         // ticks before the level is lost
+    pub gravity: Option<Direction>,  // where the rocks fall, down by default
+    pub wrap: bool,                  // the edges lead to the opposite ones
+    pub gem_points: Option<usize>,   // 1 by default
+    pub extra_points: Option<usize>, // for the gems past the quota, the gem points by default
+    pub enemy_points: Option<usize>, // for every enemy blown up, none by default
+    pub time_bonus: Option<usize>,   // points for every tick left on a win, none by default
+}
+
+impl Metadata {
+    // Returns the metadata and the rest of the level
+    pub fn parse(string: &str) -> Result<(Self, &str), String> {
+        let mut metadata = Self::default();
+        let mut rest = string.trim_start_matches(['\r', '\n']);
+
+        while let Some(line) = rest.strip_prefix('@') {
+            let (line, next) = line.split_once('\n').unwrap_or((line, ""));
+            rest = next;
+
+            let (key, value) = line.trim().split_once(' ').unwrap_or((line.trim(), ""));
+            let value = value.trim();
+            let number = || {
+                value
+                    .parse()
+                    .map_err(|_| format!("Can't parse `{value}` as `@{key}`!"))
+            };
+
+            match key {
+                "level" => metadata.title = Some(value.to_string()),
+                "quota" => metadata.quota = Some(number()?),
+                "time" => metadata.time = Some(number()?),
+                "gravity" => metadata.gravity = Some(value.parse()?),
+                "wrap" if value.is_empty() => metadata.wrap = true,
+                "wrap" => return Err(format!("`@wrap` takes no value, got `{value}`!")),
+                "gem_points" => metadata.gem_points = Some(number()?),
+                "extra_points" => metadata.extra_points = Some(number()?),
+                "enemy_points" => metadata.enemy_points = Some(number()?),
+                "time_bonus" => metadata.time_bonus = Some(number()?),
+                _ => return Err(format!("Unrecognized level entry `@{key}`!")),
+            }
+        }
+
+        Ok((metadata, rest))
+    }
+}
+
+impl fmt::Display for Metadata {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(title) = &self.title {
+            writeln!(f, "@level {title}")?;
+        }
+        if let Some(quota) = self.quota {
+            writeln!(f, "@quota {quota}")?;
+        }
+        if let Some(time) = self.time {
+            writeln!(f, "@time {time}")?;
+        }
+        if let Some(gravity) = self.gravity {
+            writeln!(f, "@gravity {gravity}")?;
+        }
+        if self.wrap {
+            writeln!(f, "@wrap")?;
+        }
+        if let Some(points) = self.gem_points {
+            writeln!(f, "@gem_points {points}")?;
+        }
+        if let Some(points) = self.extra_points {
+            writeln!(f, "@extra_points {points}")?;
+        }
+        if let Some(points) = self.enemy_points {
+            writeln!(f, "@enemy_points {points}")?;
+        }
+        if let Some(bonus) = self.time_bonus {
+            writeln!(f, "@time_bonus {bonus}")?;
+        }
+        Ok(())
+    }
+}
+
+// Levels in a pack are started with the `@level <title>` line,
+// the metadata lines right above it belong to the same level
+fn split(contents: &str) -> Vec<String> {
+    let mut levels = vec![];
+    let mut current: Vec<&str> = vec![];
+
+    for line in contents.lines() {
+        if line.starts_with("@level") {
+            let start = current
+                .iter()
+                .rposition(|l| {
+                    l.starts_with("@level") || !(l.starts_with('@') || l.trim().is_empty())
+                })
+                .map_or(0, |idx| idx + 1);
+            let mut metadata = current.split_off(start);
+            metadata.retain(|l| !l.trim().is_empty());
+
+            if !current.is_empty() {
+                levels.push(std::mem::take(&mut current));
+            }
+            current.extend(metadata);
+        }
+        current.push(line);
+    }
+    levels.push(current);
+
+    levels
+        .iter()
+        .map(|lines| lines.iter().map(|line| format!("{line}\n")).collect())
+        .collect()
+}
+
+// All levels from a file in the native format
+pub fn read(path: &str) -> Result<Vec<String>, Box<dyn Error>> {
+    let contents = fs::read_to_string(path)?;
+
+    if bdcff::is_bdcff(&contents) {
+        Ok(bdcff::convert(&contents).map_err(|e| format!("{path}: {e}"))?)
+    } else {
+        Ok(split(&contents))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{split, Metadata};
+
+    #[test]
+    fn metadata_above_the_title() {
+        let pack = "@quota 1\n@level A\n#p+#\n@time 10\n\n@level B\n#+p#\n";
+        assert_eq!(
+            split(pack),
+            ["@quota 1\n@level A\n#p+#\n", "@time 10\n@level B\n#+p#\n"]
+        );
+    }
+
+    #[test]
+    fn single_level() {
+        assert_eq!(split("@quota 1\n#p+#\n"), ["@quota 1\n#p+#\n"]);
+    }
+
+    #[test]
+    fn wrap_takes_no_value() {
+        assert!(Metadata::parse("@wrap\n#p+#\n").unwrap().0.wrap);
+        assert!(Metadata::parse("@wrap no\n#p+#\n").is_err());
+    }
+}
@@ -1,23 +1,58 @@
 mod args;
+mod bdcff;
 mod direction;
 mod editor;
+pub mod engine;
+mod export;
 mod game;
+mod generator;
+mod grid;
 mod interaction;
+mod levels;
+mod menu;
 mod objects;
+mod random;
+mod scores;
+mod spectator;
+mod watcher;
 
 pub use args::Arguments;
 use args::ProgramMode;
 use editor::Editor;
 use game::Game;
+pub use interaction::{Buffer, Input, Mode};
+use spectator::Spectator;
 use std::error::Error;
 
-type Point = (usize, usize); // (x, y)
+pub type Point = (usize, usize); // (x, y)
+
+// The objects of the previous run are replaced, by the built-in ones without a definitions file
+fn load_objects(args: &Arguments) -> Result<(), String> {
+    match &args.objects_path {
+        Some(path) => objects::registry::load(path),
+        None => {
+            objects::registry::reset();
+            Ok(())
+        }
+    }
+}
 
 pub fn run(args: &Arguments) -> Result<(), Box<dyn Error>> {
-    let mut mode = interaction::get_mode(args)?;
+    // Exporting and generating need neither a window nor a terminal
+    let mut mode = match args.program_mode {
+        ProgramMode::Export | ProgramMode::Generator => Buffer::new([]).into(),
+        _ => interaction::get_mode(args)?,
+    };
+    run_with(args, &mut mode)
+}
 
+pub fn run_with(args: &Arguments, mode: &mut Mode) -> Result<(), Box<dyn Error>> {
+    load_objects(args)?;
     match args.program_mode {
-        ProgramMode::Game => Game::new(args)?.run(&mut mode),
-        ProgramMode::Editor => Editor::new(args)?.run(&mut mode),
+        ProgramMode::Game => Game::new(args)?.run(mode),
+        ProgramMode::Editor => Editor::new(args)?.run(mode),
+        ProgramMode::Spectator => Spectator::new(args)?.run(mode),
+        ProgramMode::Export => export::run(args),
+        ProgramMode::Generator => generator::run(args),
     }
 }
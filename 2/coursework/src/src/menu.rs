@@ -0,0 +1,83 @@
+use crate::{
+    grid::Grid,
+    interaction::{Drawable, Input, Interaction, Mode},
+    Point,
+};
+use std::error::Error;
+
+pub enum Choice {
+    Select(usize),
+    Back,
+    Quit, // the window was closed
+}
+
+// A list of items drawn in place of the status, the same keys work in every mode
+#[derive(Default)]
+pub struct Menu {
+    title: String,
+    items: Vec<String>,
+    selected: usize,
+    grid: Grid, // always empty
+}
+
+impl Drawable for Menu {
+    fn get_damaged(&mut self) -> Vec<Point> {
+        vec![]
+    }
+    fn get_objects(&self) -> &Grid {
+        &self.grid
+    }
+
+    fn get_status(&self) -> String {
+        let mut status = format!("{}\n", self.title);
+        for (idx, item) in self.items.iter().enumerate() {
+            let marker = if idx == self.selected { '>' } else { ' ' };
+            status += &format!("\n{marker} {item}");
+        }
+        status + "\n\nW/R - move, Enter - select, Esc - back"
+    }
+}
+
+impl Menu {
+    pub fn new(title: &str, items: Vec<String>) -> Self {
+        Self {
+            title: title.to_string(),
+            items,
+            ..Default::default()
+        }
+    }
+
+    // The selection is kept for the next run
+    pub fn set_title(&mut self, title: &str) {
+        title.clone_into(&mut self.title);
+    }
+    pub fn set_items(&mut self, items: Vec<String>) {
+        self.items = items;
+        self.select(self.selected);
+    }
+    pub fn select(&mut self, idx: usize) {
+        self.selected = idx.min(self.items.len().saturating_sub(1));
+    }
+
+    pub fn run(&mut self, interaction: &mut Mode) -> Result<Choice, Box<dyn Error>> {
+        loop {
+            interaction.draw(self)?;
+
+            let count = self.items.len();
+            match interaction.wait_input(None) {
+                Input::Quit => return Ok(Choice::Quit),
+                Input::Esc => return Ok(Choice::Back),
+                Input::Enter | Input::Space if count > 0 => {
+                    return Ok(Choice::Select(self.selected));
+                }
+                Input::W | Input::Up if count > 0 => {
+                    self.selected = (self.selected + count - 1) % count;
+                }
+                Input::S | Input::Down if count > 0 => {
+                    self.selected = (self.selected + 1) % count;
+                }
+                _ => (),
+            }
+        }
+    }
+}
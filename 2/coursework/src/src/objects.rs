@@ -1,22 +1,35 @@
 use crate::{
-    direction::Direction,
+    direction::{Direction, Move},
     game::level::{Level, Request, State},
     Point,
 };
 use enum_dispatch::enum_dispatch;
 
+mod custom;
 mod dirt;
+mod exit;
+mod expanding_wall;
+mod firefly;
 mod gem;
 mod player;
+pub mod registry;
 mod rock;
+mod slime_wall;
+mod steel;
 mod unknown;
 mod void;
 mod wall;
 
+use custom::Custom;
 use dirt::Dirt;
+use exit::Exit;
+use expanding_wall::ExpandingWall;
+use firefly::Firefly;
 use gem::Gem;
 use player::Player;
 use rock::Rock;
+use slime_wall::SlimeWall;
+use steel::Steel;
 use unknown::Unknown;
 use void::Void;
 use wall::Wall;
@@ -26,11 +39,17 @@ use wall::Wall;
 pub enum Object {
     Gem,
     Wall,
+    Steel,
+    ExpandingWall,
+    SlimeWall,
     Dirt,
     Rock,
     Void,
     Player,
+    Firefly,
+    Exit,
     Unknown,
+    Custom,
 }
 
 impl Default for Object {
@@ -40,27 +59,27 @@ impl Default for Object {
 }
 
 impl Object {
+    // Stands in for everything outside of the level
+    pub const BORDER: Self = Self::Steel(Steel);
+
     pub fn get_all_displayable() -> Vec<Self> {
-        vec![
-            Void.into(),
-            Wall.into(),
-            Rock.into(),
-            Dirt.into(),
-            Gem.into(),
-            Player.into(),
-        ]
+        registry::get().get_objects().clone()
     }
 
     pub fn new(chr: char) -> Self {
-        match chr {
-            '+' => Gem.into(),
-            '#' => Wall.into(),
-            '*' => Dirt.into(),
-            'O' => Rock.into(),
-            ' ' => Void.into(),
-            'p' => Player.into(),
-            _ => Unknown.into(),
-        }
+        registry::get()
+            .get_objects()
+            .iter()
+            .find(|obj| obj.char() == chr)
+            .cloned()
+            .unwrap_or_else(|| Unknown.into())
+    }
+
+    // Counts towards the score of the level
+    pub fn collectible(&self) -> bool {
+        self.init()
+            .iter()
+            .any(|request| matches!(request, Request::AddMaxScore))
     }
 }
 
@@ -71,6 +90,9 @@ pub trait Labels: std::fmt::Debug {
     fn name(&self) -> String {
         format!("{self:?}").to_lowercase()
     }
+    fn sprite(&self) -> String {
+        self.name()
+    }
 }
 
 #[enum_dispatch(Object)]
@@ -87,6 +109,22 @@ pub trait Properties {
     fn can_be_broken(&self) -> bool {
         false
     }
+    // Rocks roll off rounded objects
+    fn rounded(&self) -> bool {
+        false
+    }
+    // Survives the explosions
+    fn indestructible(&self) -> bool {
+        false
+    }
+    // Worth points when blown up, crushed by the rocks
+    fn enemy(&self) -> bool {
+        false
+    }
+    // Wins the level once the quota is collected
+    fn exit(&self) -> bool {
+        false
+    }
 }
 
 #[enum_dispatch(Object)]
@@ -97,7 +135,7 @@ pub trait Behaviour {
     fn on_broken(&self, _: &Level) -> Vec<Request> {
         vec![]
     }
-    fn tick(&self, _: &Level, _: Point, _: Option<Direction>) -> Vec<Request> {
+    fn tick(&self, _: &Level, _: Point, _: Option<Move>) -> Vec<Request> {
         vec![]
     }
 }
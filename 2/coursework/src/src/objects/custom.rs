@@ -0,0 +1,82 @@
+use super::{registry, Behaviour, Labels, Level, Move, Object, Point, Properties, Request};
+
+// An object from the definitions file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Custom(pub &'static registry::Definition);
+
+impl Custom {
+    const fn definition(&self) -> &'static registry::Definition {
+        self.0
+    }
+}
+
+impl Labels for Custom {
+    fn char(&self) -> char {
+        self.definition().char
+    }
+    fn emoji(&self) -> char {
+        self.definition().emoji
+    }
+    fn name(&self) -> String {
+        self.definition().name.clone()
+    }
+    fn sprite(&self) -> String {
+        self.definition().sprite.clone()
+    }
+}
+
+impl Properties for Custom {
+    fn placeholder(&self) -> bool {
+        self.definition().placeholder
+    }
+    fn can_be_moved(&self) -> bool {
+        self.definition().can_be_moved
+    }
+    fn player(&self) -> bool {
+        self.definition()
+            .behaviour
+            .as_ref()
+            .is_some_and(Properties::player)
+    }
+    fn can_be_broken(&self) -> bool {
+        self.definition().can_be_broken
+    }
+    fn rounded(&self) -> bool {
+        self.definition().rounded
+    }
+    fn indestructible(&self) -> bool {
+        self.definition().indestructible
+    }
+    fn enemy(&self) -> bool {
+        self.definition()
+            .behaviour
+            .as_ref()
+            .is_some_and(Properties::enemy)
+    }
+    fn exit(&self) -> bool {
+        self.definition()
+            .behaviour
+            .as_ref()
+            .is_some_and(Properties::exit)
+    }
+}
+
+impl Behaviour for Custom {
+    fn init(&self) -> Vec<Request> {
+        let behaviour = self.definition().behaviour.as_ref();
+        behaviour.map_or_else(Vec::new, Behaviour::init)
+    }
+    fn on_broken(&self, level: &Level) -> Vec<Request> {
+        let behaviour = self.definition().behaviour.as_ref();
+        behaviour.map_or_else(Vec::new, |b| b.on_broken(level))
+    }
+    fn tick(&self, level: &Level, point: Point, movement: Option<Move>) -> Vec<Request> {
+        // Rocks that can't be moved stay in place
+        let behaviour = self
+            .definition()
+            .behaviour
+            .as_ref()
+            .filter(|b| !matches!(b, Object::Rock(_)) || self.can_be_moved());
+        behaviour.map_or_else(Vec::new, |b| b.tick(level, point, movement))
+    }
+}
@@ -16,6 +16,9 @@ impl Properties for Dirt {
     fn can_be_broken(&self) -> bool {
         true
     }
+    fn rounded(&self) -> bool {
+        true
+    }
 }
 
 impl Behaviour for Dirt {}
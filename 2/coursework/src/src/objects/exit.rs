@@ -0,0 +1,25 @@
+use super::{Behaviour, Labels, Properties};
+
+// Opens once the quota is collected, the level is won by stepping in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Exit;
+
+impl Labels for Exit {
+    fn char(&self) -> char {
+        'X'
+    }
+    fn emoji(&self) -> char {
+        '🚪'
+    }
+}
+
+impl Properties for Exit {
+    fn indestructible(&self) -> bool {
+        true
+    }
+    fn exit(&self) -> bool {
+        true
+    }
+}
+
+impl Behaviour for Exit {}
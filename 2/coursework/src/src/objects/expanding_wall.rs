@@ -0,0 +1,34 @@
+use super::{Behaviour, Direction, Labels, Level, Move, Point, Properties, Request};
+
+// Grows sideways into the empty space, a cell per tick
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpandingWall;
+
+impl Labels for ExpandingWall {
+    fn char(&self) -> char {
+        '~'
+    }
+    fn emoji(&self) -> char {
+        '🟪'
+    }
+}
+
+impl Properties for ExpandingWall {
+    fn rounded(&self) -> bool {
+        true
+    }
+}
+
+impl Behaviour for ExpandingWall {
+    fn tick(&self, level: &Level, point: Point, _: Option<Move>) -> Vec<Request> {
+        [Direction::Left, Direction::Right]
+            .into_iter()
+            .filter_map(|side| level.neighbour(point, side))
+            .filter(|&next| level.get_object(next).placeholder())
+            .map(|next| Request::Spawn {
+                at: next,
+                object: Self.into(),
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,75 @@
+use super::{Behaviour, Direction, Labels, Level, Move, Point, Properties, Request};
+
+// Flies along the walls on its left, blows up next to a player.
+// Only the character is saved, so the loaded ones face left
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Firefly {
+    facing: Direction,
+}
+
+impl Default for Firefly {
+    fn default() -> Self {
+        Self {
+            facing: Direction::Left,
+        }
+    }
+}
+
+impl Labels for Firefly {
+    fn char(&self) -> char {
+        'q'
+    }
+    fn emoji(&self) -> char {
+        '🪲'
+    }
+    fn name(&self) -> String {
+        "firefly".to_string()
+    }
+}
+
+impl Properties for Firefly {
+    fn enemy(&self) -> bool {
+        true
+    }
+}
+
+impl Behaviour for Firefly {
+    fn tick(&self, level: &Level, point: Point, _: Option<Move>) -> Vec<Request> {
+        let touches_player = [
+            Direction::Up,
+            Direction::Down,
+            Direction::Left,
+            Direction::Right,
+        ]
+        .into_iter()
+        .filter_map(|direction| level.neighbour(point, direction))
+        .any(|next| level.get_players().contains(&Some(next)));
+        if touches_player {
+            return vec![Request::Explode {
+                at: point,
+                scored: false,
+            }];
+        }
+
+        // Turns left whenever it can, otherwise goes ahead or turns right in place
+        let free = |direction| {
+            level
+                .neighbour(point, direction)
+                .filter(|&next| level.get_object(next).placeholder())
+        };
+        let left = self.facing.left();
+        let (facing, to) = match (free(left), free(self.facing)) {
+            (Some(to), _) => (left, to),
+            (None, Some(to)) => (self.facing, to),
+            (None, None) => (self.facing.right(), point),
+        };
+
+        vec![
+            Request::Remove { at: point },
+            Request::Spawn {
+                at: to,
+                object: Self { facing }.into(),
+            },
+        ]
+    }
+}
@@ -1,4 +1,4 @@
-use super::{Behaviour, Labels, Level, Properties, Request, State};
+use super::{Behaviour, Labels, Level, Properties, Request};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Gem;
@@ -16,6 +16,9 @@ impl Properties for Gem {
     fn can_be_broken(&self) -> bool {
         true
     }
+    fn rounded(&self) -> bool {
+        true
+    }
 }
 
 impl Behaviour for Gem {
@@ -23,11 +26,6 @@ impl Behaviour for Gem {
         vec![Request::AddMaxScore]
     }
     fn on_broken(&self, level: &Level) -> Vec<Request> {
-        let mut requests = vec![Request::AddScore];
-        if level.get_score() + 1 == *level.get_max_score() {
-            requests.push(Request::UpdateState(State::Win));
-        }
-
-        requests
+        vec![Request::AddGem, Request::AddScore(level.get_gem_points())]
     }
 }
@@ -1,4 +1,4 @@
-use super::{Behaviour, Direction, Labels, Level, Point, Properties, Request, State};
+use super::{Behaviour, Labels, Level, Move, Point, Properties, Request, State};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Player;
@@ -16,50 +16,78 @@ impl Properties for Player {
     fn player(&self) -> bool {
         true
     }
+    fn rounded(&self) -> bool {
+        true
+    }
 }
 
 impl Behaviour for Player {
-    fn tick(&self, level: &Level, _: Point, direction: Option<Direction>) -> Vec<Request> {
+    fn tick(&self, level: &Level, point: Point, movement: Option<Move>) -> Vec<Request> {
         let mut requests = vec![];
+        let (direction, snap) = match movement {
+            Some(Move::Step(direction)) => (Some(direction), false),
+            Some(Move::Snap(direction)) => (Some(direction), true),
+            None => (None, false),
+        };
+        let gravity = level.get_gravity();
 
         // to prevent the rock from falling on player when object underneath is broken
         let mut player_broke = false;
-        let mut above_point = Direction::Up.apply_to(level.get_player());
+        let mut position = point;
 
-        // Player
-        if let Some(dir) = direction {
-            let next_point = dir.apply_to(level.get_player());
+        // Player, the edges are only crossed in the wrapping levels
+        if let Some((dir, next_point)) =
+            direction.and_then(|dir| Some((dir, level.neighbour(point, dir)?)))
+        {
+            // Rocks are pushed sideways into the void
+            let pushed_to = level.neighbour(next_point, dir).filter(|&to| {
+                !snap
+                    && gravity.across().contains(&dir)
+                    && level.get_object(next_point).can_be_moved()
+                    && level.get_object(to).placeholder()
+            });
 
-            player_broke = level.get_object(next_point).can_be_broken();
-            let can_move_next = matches!(dir, Direction::Left | Direction::Right)
-                && level.get_object(next_point).can_be_moved()
-                && level.get_object(dir.apply_to(&next_point)).placeholder();
+            // The exit is open once the quota is collected
+            let exited = !snap
+                && level.get_object(next_point).exit()
+                && level.get_gems() >= level.get_quota();
+            if exited {
+                requests.push(Request::UpdateState(State::Win));
+            }
 
+            player_broke = level.get_object(next_point).can_be_broken() || exited;
             if player_broke {
                 requests.extend(level.get_object(next_point).on_broken(level));
-            } else if can_move_next {
+                if snap {
+                    requests.push(Request::Remove { at: next_point });
+                }
+            } else if let Some(to) = pushed_to {
                 requests.push(Request::MoveObj {
                     from: next_point,
-                    to: dir.apply_to(&next_point),
+                    to,
                 });
             }
 
-            if level.get_object(next_point).placeholder() || player_broke || can_move_next {
+            let moved =
+                level.get_object(next_point).placeholder() || player_broke || pushed_to.is_some();
+            if moved && !snap {
                 requests.push(Request::MoveObj {
-                    from: *level.get_player(),
+                    from: point,
                     to: next_point,
                 });
-                above_point = Direction::Up.apply_to(&next_point);
+                position = next_point;
             }
         }
 
-        // Check the rock above
-        if !player_broke && level.get_object(above_point).can_be_moved() {
-            requests.push(Request::UpdateState(State::Lose));
-            requests.push(Request::MoveObj {
-                from: above_point,
-                to: Direction::Down.apply_to(&above_point),
-            });
+        // Check the rock above, the level is lost when no players are left.
+        // Snapping leaves the player under the rock, so it still falls
+        if let Some(above_point) = level.neighbour(position, gravity.opposite()) {
+            if (!player_broke || snap) && level.get_object(above_point).can_be_moved() {
+                requests.push(Request::MoveObj {
+                    from: above_point,
+                    to: position,
+                });
+            }
         }
 
         requests
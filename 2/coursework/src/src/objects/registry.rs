@@ -0,0 +1,214 @@
+use super::{
+    Dirt, Exit, ExpandingWall, Firefly, Gem, Labels, Object, Player, Rock, SlimeWall, Steel, Void,
+    Wall,
+};
+use std::{
+    fs,
+    sync::{OnceLock, PoisonError, RwLock},
+};
+
+static BUILT_IN: OnceLock<Registry> = OnceLock::new();
+static LOADED: RwLock<Option<&'static Registry>> = RwLock::new(None);
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Definition {
+    pub name: String,
+    pub char: char,
+    pub emoji: char,
+    pub sprite: String,
+    pub behaviour: Option<Object>, // a built-in object to borrow the behaviour from
+    pub placeholder: bool,
+    pub can_be_moved: bool,
+    pub can_be_broken: bool,
+    pub rounded: bool,
+    pub indestructible: bool,
+}
+
+impl Definition {
+    // <name> <char> <emoji> <sprite> <behaviour> [flags]...
+    fn parse(line: &str) -> Result<Self, String> {
+        let mut fields = line.split_whitespace();
+        let mut field = |name: &str| {
+            fields
+                .next()
+                .ok_or_else(|| format!("Missing {name} in `{line}`!"))
+        };
+        let single_char = |name: &str, value: &str| {
+            let mut chars = value.chars();
+            match (chars.next(), chars.next()) {
+                (Some(chr), None) => Ok(chr),
+                _ => Err(format!(
+                    "Expected a single character as {name}, got `{value}`!"
+                )),
+            }
+        };
+
+        let name = field("name")?.to_string();
+        let char = single_char("character", field("character")?)?;
+        let emoji = single_char("emoji", field("emoji")?)?;
+        let sprite = field("sprite")?.to_string();
+        let behaviour = match field("behaviour")? {
+            "none" => None,
+            "gem" => Some(Gem.into()),
+            "rock" => Some(Rock.into()),
+            "player" => Some(Player.into()),
+            "expanding" => Some(ExpandingWall.into()),
+            "slime" => Some(SlimeWall.into()),
+            "exit" => Some(Exit.into()),
+            other => return Err(format!("Unknown behaviour `{other}`!")),
+        };
+
+        let mut definition = Self {
+            name,
+            char,
+            emoji,
+            sprite,
+            behaviour,
+            placeholder: false,
+            can_be_moved: false,
+            can_be_broken: false,
+            rounded: false,
+            indestructible: false,
+        };
+
+        for flag in fields {
+            match flag {
+                "placeholder" => definition.placeholder = true,
+                "can_be_moved" => definition.can_be_moved = true,
+                "can_be_broken" => definition.can_be_broken = true,
+                "rounded" => definition.rounded = true,
+                "indestructible" => definition.indestructible = true,
+                _ => return Err(format!("Unknown flag `{flag}`!")),
+            }
+        }
+
+        Ok(definition)
+    }
+}
+
+pub struct Registry {
+    objects: Vec<Object>, // in the editor order
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Self {
+            objects: vec![
+                Void.into(),
+                Wall.into(),
+                Steel.into(),
+                ExpandingWall.into(),
+                SlimeWall.into(),
+                Rock.into(),
+                Dirt.into(),
+                Gem.into(),
+                Player.into(),
+                Firefly::default().into(),
+                Exit.into(),
+            ],
+        }
+    }
+}
+
+pub fn get() -> &'static Registry {
+    let loaded = *LOADED.read().unwrap_or_else(PoisonError::into_inner);
+    loaded.unwrap_or_else(|| BUILT_IN.get_or_init(Registry::default))
+}
+
+pub fn reset() {
+    *LOADED.write().unwrap_or_else(PoisonError::into_inner) = None;
+}
+
+impl Registry {
+    // The built-in objects with the definitions of a file
+    fn parse(contents: &str) -> Result<Self, String> {
+        let mut registry = Self::default();
+        let mut defined = vec![];
+
+        let lines = contents.lines().map(str::trim);
+        for line in lines.filter(|l| !l.is_empty() && !l.starts_with('#')) {
+            let definition: &'static Definition = Box::leak(Box::new(Definition::parse(line)?));
+            if defined.contains(&definition.char) {
+                return Err(format!("`{}` is defined twice!", definition.char));
+            }
+            defined.push(definition.char);
+            let object = Object::from(super::Custom(definition));
+
+            // Custom objects replace the built-in ones with the same character
+            match registry
+                .objects
+                .iter()
+                .position(|o| o.char() == definition.char)
+            {
+                Some(idx) => registry.objects[idx] = object,
+                None => registry.objects.push(object),
+            }
+        }
+
+        Ok(registry)
+    }
+
+    pub fn get_objects(&self) -> &Vec<Object> {
+        &self.objects
+    }
+}
+
+// Replaces the objects created from now on, the existing ones keep their definitions.
+// Loaded once per run, so the replaced registries are left behind
+pub fn load(path: &str) -> Result<(), String> {
+    let contents = fs::read_to_string(path).map_err(|e| format!("{path}: {e}"))?;
+    let registry = Registry::parse(&contents).map_err(|e| format!("{path}: {e}"))?;
+
+    *LOADED.write().unwrap_or_else(PoisonError::into_inner) = Some(Box::leak(Box::new(registry)));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::Properties;
+
+    #[test]
+    fn definition() {
+        let definition = Definition::parse("ruby $ 🔴 gem gem can_be_broken rounded").unwrap();
+        assert_eq!(
+            definition,
+            Definition {
+                name: "ruby".to_string(),
+                char: '$',
+                emoji: '🔴',
+                sprite: "gem".to_string(),
+                behaviour: Some(Gem.into()),
+                placeholder: false,
+                can_be_moved: false,
+                can_be_broken: true,
+                rounded: true,
+                indestructible: false,
+            }
+        );
+    }
+
+    #[test]
+    fn replaces_built_in() {
+        let registry = Registry::parse("pillar # 🗿 wall none").unwrap();
+        let objects = registry.get_objects();
+        assert_eq!(objects.len(), Registry::default().get_objects().len());
+        let wall = objects.iter().find(|o| o.char() == '#').unwrap();
+        assert_eq!(wall.name(), "pillar");
+        assert!(!wall.rounded());
+    }
+
+    #[test]
+    fn defined_twice() {
+        let contents = "pillar I 🗿 wall none\ncolumn I 🏛 wall none rounded";
+        assert!(Registry::parse(contents).is_err());
+    }
+
+    #[test]
+    fn errors() {
+        assert!(Definition::parse("ruby $ 🔴 gem shiny").is_err());
+        assert!(Definition::parse("ruby $ 🔴 gem gem sparkling").is_err());
+        assert!(Definition::parse("ruby $ 🔴").is_err());
+        assert!(Definition::parse("ruby $$ 🔴 gem gem").is_err());
+    }
+}
@@ -1,4 +1,4 @@
-use super::{Behaviour, Direction, Labels, Level, Point, Properties, Request};
+use super::{Behaviour, Labels, Level, Move, Point, Properties, Request};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Rock;
@@ -16,28 +16,56 @@ impl Properties for Rock {
     fn can_be_moved(&self) -> bool {
         true
     }
+    fn rounded(&self) -> bool {
+        true
+    }
 }
 
 impl Behaviour for Rock {
-    fn tick(&self, level: &Level, (x, y): Point, _: Option<Direction>) -> Vec<Request> {
-        if (x, y) == *level.get_player() || (x, y) == Direction::Up.apply_to(level.get_player()) {
+    fn tick(&self, level: &Level, point: Point, _: Option<Move>) -> Vec<Request> {
+        let gravity = level.get_gravity();
+
+        // Players handle the rocks above them
+        if level.get_players().iter().flatten().any(|&player| {
+            point == player || level.neighbour(player, gravity.opposite()) == Some(point)
+        }) {
+            return vec![];
+        }
+
+        let Some(below) = level.neighbour(point, gravity) else {
             return vec![];
+        };
+
+        // Enemies are crushed by the falling rocks, the resting ones let them pass
+        if level.get_object(below).enemy() && level.is_falling(point) {
+            return vec![Request::Explode {
+                at: below,
+                scored: true,
+            }];
         }
 
-        if level.get_object((x, y + 1)).placeholder() {
+        if level.get_object(below).placeholder() {
             return vec![Request::MoveObj {
-                from: (x, y),
-                to: (x, y + 1),
+                from: point,
+                to: below,
             }];
         }
 
-        for side in [x - 1, x + 1] {
-            if level.get_object((side, y)).placeholder()
-                && level.get_object((side, y + 1)).placeholder()
-            {
+        if !level.get_object(below).rounded() {
+            return vec![];
+        }
+
+        for side in gravity.across() {
+            let (Some(next), Some(next_below)) =
+                (level.neighbour(point, side), level.neighbour(below, side))
+            else {
+                continue;
+            };
+
+            if level.get_object(next).placeholder() && level.get_object(next_below).placeholder() {
                 return vec![Request::MoveObj {
-                    from: (x, y),
-                    to: (side, y + 1),
+                    from: point,
+                    to: next_below,
                 }];
             }
         }
@@ -0,0 +1,42 @@
+use super::{Behaviour, Labels, Level, Move, Point, Properties, Request};
+
+const SEEP_TICKS: usize = 8;
+
+// Lets the rocks through to the empty space below, once in a while
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlimeWall;
+
+impl Labels for SlimeWall {
+    fn char(&self) -> char {
+        '&'
+    }
+    fn emoji(&self) -> char {
+        '🟩'
+    }
+}
+
+impl Properties for SlimeWall {}
+
+impl Behaviour for SlimeWall {
+    fn tick(&self, level: &Level, point: Point, _: Option<Move>) -> Vec<Request> {
+        let gravity = level.get_gravity();
+        let (Some(above), Some(below)) = (
+            level.neighbour(point, gravity.opposite()),
+            level.neighbour(point, gravity),
+        ) else {
+            return vec![];
+        };
+
+        if !level.get_ticks().is_multiple_of(SEEP_TICKS)
+            || !level.get_object(above).can_be_moved()
+            || !level.get_object(below).placeholder()
+        {
+            return vec![];
+        }
+
+        vec![Request::MoveObj {
+            from: above,
+            to: below,
+        }]
+    }
+}
@@ -0,0 +1,22 @@
+use super::{Behaviour, Labels, Properties};
+
+// Lines the caves, not rounded unlike the brick wall
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Steel;
+
+impl Labels for Steel {
+    fn char(&self) -> char {
+        '%'
+    }
+    fn emoji(&self) -> char {
+        '🔲'
+    }
+}
+
+impl Properties for Steel {
+    fn indestructible(&self) -> bool {
+        true
+    }
+}
+
+impl Behaviour for Steel {}
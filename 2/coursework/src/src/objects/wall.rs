@@ -1,5 +1,6 @@
 use super::{Behaviour, Labels, Properties};
 
+// The brick wall, rocks roll off it
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Wall;
 
@@ -12,5 +13,10 @@ impl Labels for Wall {
     }
 }
 
-impl Properties for Wall {}
+impl Properties for Wall {
+    fn rounded(&self) -> bool {
+        true
+    }
+}
+
 impl Behaviour for Wall {}
@@ -0,0 +1,33 @@
+// The pseudo-random generator of the original Boulder Dash, used to fill the caves
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Random {
+    seed1: u8,
+    seed2: u8,
+}
+
+impl Random {
+    pub const fn new(seed: u8) -> Self {
+        Self {
+            seed1: 0,
+            seed2: seed,
+        }
+    }
+
+    pub fn next(&mut self) -> u8 {
+        let temp1 = (self.seed1 & 0x01) << 7;
+        let temp2 = (self.seed2 >> 1) & 0x7f;
+
+        let result = u16::from(self.seed2) + u16::from(self.seed2 & 0x01) * 0x80;
+        let carry = result >> 8;
+        let result = (result & 0xff) + carry + 0x13;
+        let carry = result >> 8;
+        self.seed2 = (result & 0xff) as u8;
+
+        let result = u16::from(self.seed1) + carry + u16::from(temp1);
+        let carry = result >> 8;
+        let result = (result & 0xff) + carry + u16::from(temp2);
+        self.seed1 = (result & 0xff) as u8;
+
+        self.seed1
+    }
+}
@@ -0,0 +1,156 @@
+use std::{
+    collections::BTreeMap,
+    env, fmt, fs, io,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+const ENTRIES_PER_LEVEL: usize = 10;
+
+// FNV-1a, stable between builds unlike the std hasher
+pub fn hash(contents: &str) -> u64 {
+    contents.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+fn data_dir() -> Option<PathBuf> {
+    let dir = env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+        .or_else(|| env::var_os("APPDATA").map(PathBuf::from))?;
+
+    Some(dir.join("boulder_dash"))
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub score: usize,
+    pub ticks: usize,
+    pub date: u64, // seconds since the Unix epoch
+}
+
+impl Entry {
+    pub fn new(score: usize, ticks: usize) -> Self {
+        let date = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+
+        Self { score, ticks, date }
+    }
+
+    // Higher score first, then the faster one
+    fn is_better_than(&self, other: &Self) -> bool {
+        (self.score, other.ticks) > (other.score, self.ticks)
+    }
+}
+
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Days to the civil date, see http://howardhinnant.github.io/date_algorithms.html
+        let days = self.date / 86400 + 719_468;
+        let era = days / 146_097;
+        let day_of_era = days % 146_097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month_index = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+        let month = if month_index < 10 {
+            month_index + 3
+        } else {
+            month_index - 9
+        };
+        let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+        write!(
+            f,
+            "{} in {} ticks ({year}-{month:02}-{day:02})",
+            self.score, self.ticks
+        )
+    }
+}
+
+// Tables of the best runs keyed by the hash of the level contents
+#[derive(Default)]
+pub struct HighScores {
+    path: Option<PathBuf>,
+    tables: BTreeMap<u64, Vec<Entry>>,
+}
+
+impl HighScores {
+    // The user's data directory holds the scores unless another file is given
+    pub fn load(path: Option<&str>) -> io::Result<Self> {
+        let path = path
+            .map(PathBuf::from)
+            .or_else(|| data_dir().map(|dir| dir.join("scores")));
+        let mut scores = Self {
+            path,
+            ..Default::default()
+        };
+
+        let contents = match scores.path.as_ref().map(fs::read_to_string) {
+            Some(Ok(contents)) => contents,
+            Some(Err(e)) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            _ => return Ok(scores),
+        };
+
+        for line in contents.lines() {
+            let fields: Vec<_> = line.split_whitespace().collect();
+            let [hash, score, ticks, date] = fields[..] else {
+                continue;
+            };
+            let (Ok(hash), Ok(score), Ok(ticks), Ok(date)) = (
+                u64::from_str_radix(hash, 16),
+                score.parse(),
+                ticks.parse(),
+                date.parse(),
+            ) else {
+                continue;
+            };
+
+            scores
+                .tables
+                .entry(hash)
+                .or_default()
+                .push(Entry { score, ticks, date });
+        }
+
+        Ok(scores)
+    }
+
+    fn save(&self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let mut contents = String::new();
+        for (hash, table) in &self.tables {
+            for Entry { score, ticks, date } in table {
+                contents += &format!("{hash:016x} {score} {ticks} {date}\n");
+            }
+        }
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, contents)
+    }
+
+    pub fn get_best(&self, hash: u64) -> Option<&Entry> {
+        self.tables.get(&hash)?.first()
+    }
+
+    pub fn record(&mut self, hash: u64, entry: Entry) -> io::Result<()> {
+        let table = self.tables.entry(hash).or_default();
+        let position = table
+            .iter()
+            .position(|other| entry.is_better_than(other))
+            .unwrap_or(table.len());
+
+        table.insert(position, entry);
+        table.truncate(ENTRIES_PER_LEVEL);
+
+        self.save()
+    }
+}
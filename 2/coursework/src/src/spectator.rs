@@ -0,0 +1,235 @@
+use crate::{
+    args::Arguments,
+    grid::Grid,
+    interaction::{Drawable, Input, Interaction, Mode},
+    objects::Object,
+    Point,
+};
+use std::{
+    collections::HashSet,
+    error::Error,
+    io::{self, BufRead, BufReader, Write},
+    net::TcpStream,
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+    time::Duration,
+};
+
+// A state change sent from the game to the spectators: `g <width> <height>` of the grid,
+// `c <x> <y> <char>` for every damaged cell, `s <line>` for every status line, `e` at the end
+#[derive(Default)]
+pub struct Frame {
+    pub size: (usize, usize),
+    pub cells: Vec<(Point, char)>,
+    pub status: String,
+}
+
+impl Frame {
+    pub fn write(&self, writer: &mut impl Write) -> io::Result<()> {
+        let mut contents = format!("g {} {}\n", self.size.0, self.size.1);
+        for ((x, y), chr) in &self.cells {
+            contents += &format!("c {x} {y} {chr}\n");
+        }
+        for line in self.status.lines() {
+            contents += &format!("s {line}\n");
+        }
+        contents += "e\n";
+
+        writer.write_all(contents.as_bytes())
+    }
+
+    // None when the stream has ended
+    pub fn read(reader: &mut impl BufRead) -> io::Result<Option<Self>> {
+        let invalid = |line: &str| io::Error::new(io::ErrorKind::InvalidData, line.to_string());
+        let mut frame = Self::default();
+
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line)? == 0 {
+                return Ok(None);
+            }
+            let line = line.trim_end_matches(['\r', '\n']);
+
+            match line.split_once(' ') {
+                None if line == "e" => return Ok(Some(frame)),
+                Some(("s", status)) => {
+                    frame.status += status;
+                    frame.status.push('\n');
+                }
+                Some(("g", size)) => {
+                    let mut parts = size.split(' ').map(|s| s.parse().ok());
+                    match (parts.next().flatten(), parts.next().flatten(), parts.next()) {
+                        (Some(width), Some(height), None) => frame.size = (width, height),
+                        _ => return Err(invalid(line)),
+                    }
+                }
+                Some(("c", cell)) => {
+                    let mut parts = cell.splitn(3, ' ');
+                    let mut coordinate = || parts.next().and_then(|s| s.parse().ok());
+                    let point = (coordinate(), coordinate());
+                    let chr = parts.next().and_then(|s| s.chars().next());
+
+                    match (point, chr) {
+                        ((Some(x), Some(y)), Some(chr)) => frame.cells.push(((x, y), chr)),
+                        _ => return Err(invalid(line)),
+                    }
+                }
+                _ => return Err(invalid(line)),
+            }
+        }
+    }
+}
+
+pub struct Spectator {
+    address: String,
+    status: String,
+    connected: bool,
+    frames: Receiver<Frame>,
+    damaged: HashSet<Point>,
+    matrix: Grid,
+}
+
+impl Drawable for Spectator {
+    fn get_damaged(&mut self) -> Vec<Point> {
+        std::mem::take(&mut self.damaged).into_iter().collect()
+    }
+    fn get_objects(&self) -> &Grid {
+        &self.matrix
+    }
+
+    fn get_status(&self) -> String {
+        let connection = if self.connected {
+            "Spectating"
+        } else {
+            "Disconnected from"
+        };
+        format!("{connection} {}\n{}", self.address, self.status)
+    }
+}
+
+impl Spectator {
+    pub fn new(args: &Arguments) -> io::Result<Self> {
+        let address = args.address.clone().unwrap_or_default();
+        let mut reader = BufReader::new(TcpStream::connect(&address)?);
+
+        let (frames_tx, frames) = mpsc::channel();
+        thread::spawn(move || {
+            while let Ok(Some(frame)) = Frame::read(&mut reader) {
+                if frames_tx.send(frame).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Self {
+            address,
+            status: String::new(),
+            connected: true,
+            frames,
+            damaged: HashSet::new(),
+            matrix: Grid::default(),
+        })
+    }
+
+    fn apply(&mut self, frame: Frame) {
+        // The game has moved to a level of another size, everything is drawn again
+        let (width, height) = frame.size;
+        if (width, height) != (self.matrix.get_width(), self.matrix.get_height()) {
+            self.matrix.resize(width, height);
+            self.damaged = self.matrix.points().collect();
+        }
+
+        for (point, chr) in frame.cells {
+            if self.matrix.contains(point) {
+                self.matrix.set(point, Object::new(chr));
+                self.damaged.insert(point);
+            }
+        }
+        self.status = frame.status;
+    }
+
+    pub fn run(&mut self, interaction: &mut Mode) -> Result<(), Box<dyn Error>> {
+        interaction.draw(self)?;
+
+        loop {
+            // The frames arrive from another thread, so the wait is limited
+            let input = interaction.wait_input(Some(Duration::from_millis(20)));
+            if matches!(input, Input::Quit | Input::Q) {
+                return Ok(());
+            }
+
+            let mut changed = false;
+            loop {
+                match self.frames.try_recv() {
+                    Ok(frame) => {
+                        self.apply(frame);
+                        changed = true;
+                    }
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => {
+                        changed |= self.connected;
+                        self.connected = false;
+                        break;
+                    }
+                }
+            }
+
+            if changed {
+                interaction.draw(self)?;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::Labels;
+    use std::net::TcpListener;
+
+    fn frame(rows: &[&str]) -> Frame {
+        let cells = rows
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| row.chars().enumerate().map(move |(x, chr)| ((x, y), chr)))
+            .collect();
+        Frame {
+            size: (rows[0].len(), rows.len()),
+            cells,
+            status: format!("Level {}\n", rows.len()),
+        }
+    }
+
+    fn rows(matrix: &Grid) -> Vec<String> {
+        matrix
+            .rows()
+            .map(|row| row.iter().map(Object::char).collect())
+            .collect()
+    }
+
+    #[test]
+    fn loopback() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let args = Arguments {
+            address: Some(listener.local_addr().unwrap().to_string()),
+            ..Default::default()
+        };
+        let mut spectator = Spectator::new(&args).unwrap();
+        let (mut stream, _) = listener.accept().unwrap();
+
+        frame(&["###", "#p#", "###"]).write(&mut stream).unwrap();
+        frame(&["%+", "%%"]).write(&mut stream).unwrap();
+        drop(stream);
+
+        spectator.apply(spectator.frames.recv().unwrap());
+        assert_eq!(rows(&spectator.matrix), ["###", "#p#", "###"]);
+        assert_eq!(spectator.status, "Level 3\n");
+
+        // The next level is smaller
+        spectator.apply(spectator.frames.recv().unwrap());
+        assert_eq!(rows(&spectator.matrix), ["%+", "%%"]);
+        assert_eq!(spectator.get_damaged().len(), 4);
+
+        assert!(spectator.frames.recv().is_err());
+    }
+}
@@ -0,0 +1,43 @@
+use std::{
+    fs,
+    time::{Duration, SystemTime},
+};
+
+// How often the files are checked
+pub const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+// Notices the changes of files on disk by polling their modification times
+#[derive(Default)]
+pub struct Watcher {
+    files: Vec<(String, Option<SystemTime>)>,
+}
+
+fn modified(path: &str) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+impl Watcher {
+    pub fn new(paths: &[String]) -> Self {
+        Self {
+            files: paths.iter().map(|p| (p.clone(), modified(p))).collect(),
+        }
+    }
+
+    // Nothing has to be polled otherwise
+    pub fn is_watching(&self) -> bool {
+        !self.files.is_empty()
+    }
+
+    // The paths of the changed files, every change is reported once
+    pub fn changed(&mut self) -> Vec<String> {
+        let mut changed = vec![];
+        for (path, time) in &mut self.files {
+            let new_time = modified(path);
+            if new_time != *time {
+                *time = new_time;
+                changed.push(path.clone());
+            }
+        }
+        changed
+    }
+}
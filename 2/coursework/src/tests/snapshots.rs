@@ -0,0 +1,235 @@
+use boulder_dash::{run_with, Arguments, Buffer, Input, Mode};
+use std::{env, error::Error, fs, path::PathBuf, process};
+
+fn temp_dir() -> PathBuf {
+    env::temp_dir().join(format!("boulder_dash_tests_{}", process::id()))
+}
+
+// Runs a level with scripted input and returns the drawn frames,
+// every test keeps its own high scores out of the user's data directory
+fn try_run(
+    name: &str,
+    level: &str,
+    flags: &[&str],
+    input: Vec<Input>,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let dir = temp_dir();
+    fs::create_dir_all(&dir).unwrap();
+
+    let path = dir.join(name);
+    fs::write(&path, level).unwrap();
+    let path = path.to_string_lossy().into_owned();
+    let scores = format!("{path}.scores");
+    let flags = ["-l", &path, "-d", "200", "--scores", &scores]
+        .into_iter()
+        .chain(flags.iter().copied());
+    let args = Arguments::parse(flags.map(|flag| flag.to_string())).unwrap();
+
+    let mut mode = Mode::from(Buffer::new(input));
+    run_with(&args, &mut mode)?;
+    match mode {
+        Mode::Buffer(buffer) => Ok(buffer.get_frames().to_vec()),
+        _ => unreachable!(),
+    }
+}
+
+fn run(name: &str, level: &str, flags: &[&str], input: Vec<Input>) -> Vec<String> {
+    try_run(name, level, flags, input).unwrap()
+}
+
+fn play(name: &str, level: &str, input: Vec<Input>) -> Vec<String> {
+    run(name, level, &[], input)
+}
+
+#[test]
+fn start() {
+    let frames = play("start", "#####\n#p +#\n#####\n", vec![Input::Enter]);
+    assert_eq!(
+        frames,
+        [
+            "\nBoulder Dash\n\n> Play\n  Settings\n  Quit\n\nW/R - move, Enter - select, Esc - back",
+            "#####\n#p +#\n#####\n\nGems: 0/1\nScore: 0\nDelay: 200ms\nPaused: no",
+        ]
+    );
+}
+
+#[test]
+fn ticks_follow_the_timeouts() {
+    let input = vec![Input::Enter, Input::D, Input::Unknown];
+    let frames = play("step", "#####\n#p +#\n#####\n", input);
+    assert_eq!(
+        frames.last().unwrap(),
+        "#####\n# p+#\n#####\n\nGems: 0/1\nScore: 0\nDelay: 200ms\nPaused: no"
+    );
+}
+
+#[test]
+fn win() {
+    let input = vec![Input::Enter, Input::D, Input::D, Input::Unknown];
+    let frames = play("win", "#####\n#p +#\n#%%%#\n", input);
+    // The best score line carries the date
+    assert!(frames
+        .last()
+        .unwrap()
+        .starts_with("#####\n#  p#\n#%%%#\n\nYou have won!\nScore: 1 in 2 ticks\n"));
+}
+
+#[test]
+fn falling_rock() {
+    let input = vec![Input::Enter, Input::S, Input::Unknown, Input::Unknown];
+    let frames = play("rock", "#####\n# O #\n#   #\n#p  #\n#####\n", input);
+    assert_eq!(
+        frames.last().unwrap(),
+        "#####\n#   #\n#   #\n#pO #\n#####\n\nGems: 0/0\nScore: 0\nDelay: 200ms\nPaused: no"
+    );
+}
+
+#[test]
+fn save() {
+    let input = vec![Input::Enter, Input::D, Input::Unknown, Input::E];
+    play("save", "#####\n#p +#\n#####\n", input);
+    let save = fs::read_to_string(temp_dir().join("save.save")).unwrap();
+    assert_eq!(
+        save,
+        "level 0\nhash d9278b95166ae6fc\nticks 2\nscore 0\ngems 0\nmax_score 1\nstate none\n\
+        players 2,1\nstats 1 0 0 0\ndirections right\n\n#####\n# p+#\n#####\n"
+    );
+}
+
+#[test]
+fn editor_skips_empty_pages() {
+    let pack = "@level A\n#p+#\n@level B\n#+p#";
+    let input = vec![Input::PageDown, Input::PageDown, Input::Q];
+    run("pages", pack, &["-r", "e"], input);
+    assert_eq!(fs::read_to_string(temp_dir().join("pages")).unwrap(), pack);
+}
+
+#[test]
+fn stats() {
+    let stats = temp_dir().join("stats.json");
+    let flags = ["-S", stats.to_str().unwrap()];
+    run(
+        "stats",
+        "%%%%%\n%p +%\n%%%%%\n",
+        &flags,
+        vec![Input::Enter, Input::D, Input::D],
+    );
+    // The first move starts the level right away, the second one waits for the next tick
+    let stats = fs::read_to_string(stats).unwrap();
+    assert!(stats.contains("\"outcome\": \"win\", \"ticks\": 2, \"seconds\": 0.200,"));
+}
+
+#[test]
+fn explosion() {
+    let level = "%%%%%%\n%*O**%\n%*p**%\n%****%\n%%%%%%\n";
+    let frames = play("explosion", level, vec![Input::Enter, Input::W]);
+    assert_eq!(
+        frames.last().unwrap(),
+        "%%%%%%\n%   *%\n%   *%\n%   *%\n%%%%%%\n\nYou have lost!\nR - reload"
+    );
+}
+
+#[test]
+fn snap_under_rock() {
+    let level = "%%%%%%\n%%O%%%\n%%p++%\n%%%%%%\n";
+    let input = vec![Input::Enter, Input::Snap(Box::new(Input::D))];
+    let frames = play("snap", level, input);
+    assert_eq!(
+        frames.last().unwrap(),
+        "%%%%%%\n%% %%%\n%%  +%\n%%%%%%\n\nYou have lost!\nR - reload"
+    );
+}
+
+#[test]
+fn extra_points_until_the_exit() {
+    let level = "@quota 1\n@extra_points 5\n%%%%%%%\n%p++X %\n%%%%%%%\n";
+    let input = vec![Input::Enter, Input::D, Input::D, Input::D, Input::Unknown];
+    let frames = play("exit", level, input);
+    assert!(frames
+        .last()
+        .unwrap()
+        .starts_with("%%%%%%%\n%   p %\n%%%%%%%\n\nYou have won!\nScore: 6 in 3 ticks\n"));
+}
+
+#[test]
+fn zero_quota() {
+    let input = vec![Input::Enter, Input::D, Input::Unknown];
+    let frames = play("quota", "@quota 0\n%%%%%\n%p++%\n%#%%%\n", input);
+    assert!(frames
+        .last()
+        .unwrap()
+        .starts_with("%%%%%\n% p+%\n%#%%%\n\nYou have won!\nScore: 1 in 1 ticks\n"));
+}
+
+#[test]
+fn firefly_follows_the_walls() {
+    let level = "%%%%%%%\n%q  %p%\n% % %%%\n%   %%%\n%%%%%%%\n";
+    let mut input = vec![Input::Enter, Input::S];
+    input.extend((0..5).map(|_| Input::Unknown));
+    // Around the pillar with the wall on its left, a cell per tick
+    let frames = play("firefly", level, input);
+    assert_eq!(
+        frames.last().unwrap(),
+        "%%%%%%%\n%  q%p%\n% % %%%\n%   %%%\n%%%%%%%\n\nGems: 0/0\nScore: 0\nDelay: 200ms\nPaused: no"
+    );
+}
+
+#[test]
+fn crushed_enemy() {
+    let level = "@enemy_points 10\n%%%%%%%\n%p%O%%%\n%%% %%%\n%%%q%%%\n%%%%%%%\n";
+    let input = vec![Input::Enter, Input::S, Input::Unknown, Input::Unknown];
+    let frames = play("enemy", level, input);
+    assert_eq!(
+        frames.last().unwrap(),
+        "%%%%%%%\n%p% %%%\n%%% %%%\n%%% %%%\n%%%%%%%\n\nGems: 0/0\nScore: 10\nDelay: 200ms\nPaused: no"
+    );
+}
+
+#[test]
+fn enemy_attack_scores_nothing() {
+    let level = "@enemy_points 10\n%%%%%%\n%p%pq%\n%%%%%%\n";
+    let frames = play(
+        "attack",
+        level,
+        vec![Input::Enter, Input::S, Input::Unknown],
+    );
+    assert_eq!(
+        frames.last().unwrap(),
+        "%%%%%%\n%p%  %\n%%%%%%\n\nGems: 0/0\nScore: 0\nDelay: 200ms\nPaused: no\nPlayers: 1/2"
+    );
+}
+
+#[test]
+fn stats_write_error() {
+    // A directory can't be written as the stats file
+    let dir = temp_dir().to_string_lossy().into_owned();
+    let input = vec![Input::Enter, Input::D, Input::D, Input::Unknown];
+    let frames = run(
+        "unwritable",
+        "%%%%%\n%p +%\n%%%%%\n%%%%%\n",
+        &["-S", &dir],
+        input,
+    );
+    let last = frames.last().unwrap();
+    assert!(last.starts_with("%%%%%\n%  p%\n%%%%%\n%%%%%\n\nYou have won!\n"));
+    assert!(last.contains("\nCan't save the stats: "));
+}
+
+#[test]
+fn resume() {
+    let level = "#####\n#p +#\n#%#%#\n";
+    play(
+        "resume",
+        level,
+        vec![Input::Enter, Input::D, Input::Unknown, Input::E],
+    );
+    let frames = run("resume", level, &["-c"], vec![Input::Enter]);
+    assert_eq!(
+        frames.last().unwrap(),
+        "#####\n# p+#\n#%#%#\n\nGems: 0/1\nScore: 0\nDelay: 200ms\nPaused: no"
+    );
+
+    // The level has been edited since the save
+    let edited = "#####\n#p ++#\n#%#%#\n";
+    assert!(try_run("resume", edited, &["-c"], vec![]).is_err());
+}